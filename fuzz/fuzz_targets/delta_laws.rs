@@ -0,0 +1,39 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rel_ptr::laws::*;
+use rel_ptr::Auto;
+
+use std::convert::TryInto;
+use std::num::*;
+
+const BUFFER: usize = 4096;
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 12 {
+        return;
+    }
+
+    let buffer = [0_u8; BUFFER];
+    let a = u16::from_le_bytes(data[0..2].try_into().unwrap()) as usize % (BUFFER + 1);
+    let b = u16::from_le_bytes(data[2..4].try_into().unwrap()) as usize % (BUFFER + 1);
+    let del = i64::from_le_bytes(data[4..12].try_into().unwrap()) as isize;
+
+    macro_rules! check {
+        ($($type:ty),*) => {$(
+            check_delta_pair::<$type>(&buffer, a, b);
+            check_integer_delta_pair::<$type>(&buffer, a, b);
+            check_integer_delta_value::<$type>(del);
+        )*};
+    }
+
+    macro_rules! check_nullable {
+        ($($type:ty),*) => {$(
+            check!($type);
+            check_nullable_at::<$type>(&buffer, a);
+        )*};
+    }
+
+    check_nullable!(i8, i16, i32, i64, i128, isize, Auto);
+    check!(NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize);
+});
@@ -0,0 +1,43 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rel_ptr::{IntegerDelta, RelPtr};
+
+const LEN: usize = 512;
+
+struct Block {
+    ptrs: [RelPtr<[u8], i8>; LEN],
+    data: [u8; LEN],
+}
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 6 {
+        return;
+    }
+
+    let mut block = Block {
+        ptrs: [RelPtr::null(); LEN],
+        data: [0; LEN],
+    };
+
+    let index = u16::from_le_bytes([data[0], data[1]]) as usize % LEN;
+    let start = u16::from_le_bytes([data[2], data[3]]) as usize % LEN;
+    let len = u16::from_le_bytes([data[4], data[5]]) as usize % (LEN - start + 1);
+
+    let ptr = &mut block.ptrs[index];
+    let target = &mut block.data[start..start + len];
+    let del = target.as_ptr() as isize - ptr as *const _ as isize;
+
+    // `set` fails exactly when the offset doesn't fit, and leaves the pointer unchanged
+    match ptr.set(target) {
+        Ok(()) => unsafe {
+            assert_eq!(ptr.offset().to_isize(), del);
+            assert_eq!(ptr.as_ref_unchecked().as_ptr(), block.data[start..].as_ptr());
+            assert_eq!(ptr.as_ref_unchecked().len(), len);
+        },
+        Err(_) => {
+            assert!(i8::from_isize(del).is_err());
+            assert!(ptr.is_null());
+        },
+    }
+});
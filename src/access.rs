@@ -0,0 +1,376 @@
+use super::{RelPtr, MetaData, Delta, Nullable, RelTrace, RelVisitor, RelVisitorMut};
+
+use core::mem::MaybeUninit;
+
+/**
+ * A relative pointer that can only be used to read its pointee, like `&T`,
+ * but invariant in `T`
+ *
+ * `RelPtr` can give out both shared and mutable references, so it can't say whether
+ * a field is meant to be read-only. `RelConst` only has the accessors for shared references,
+ * and it is `Send` and `Sync` when `T` is `Sync`, like `&T` (see `RelMut` for how this works
+ * with threads). It can be set from a shared
+ * reference, and it is `Copy`.
+ *
+ * `RelConst` has the same layout as `RelPtr`, so it can be used in relocatable images,
+ * and `RelConst::from_rel_ptr` gives a read-only view of a `RelPtr` in place.
+ *
+ * Unlike `&T`, it is invariant in `T`, so a `RelConst<&'static str>` can't be used
+ * where a `RelConst<&'a str>` is expected. This can't be fixed without changing
+ * the layout: the metadata is stored as `T::Data`, and the compiler makes every
+ * type parameter that is used through an associated type invariant. Use
+ * `RelConst::set` to point a new `RelConst` at the same value instead.
+ *
+ * ```compile_fail
+ * use rel_ptr::RelConst;
+ *
+ * fn shorten<'a>(ptr: RelConst<&'static str>) -> RelConst<&'a str> {
+ *     ptr
+ * }
+ * ```
+ *
+ * ```rust
+ * # fn main() {
+ * use rel_ptr::RelConst;
+ *
+ * struct Name {
+ *     value: [u8; 6],
+ *     ptr: RelConst<[u8], i8>,
+ * }
+ *
+ * let mut name = Name { value: *b"Ferris", ptr: RelConst::null() };
+ *
+ * name.ptr.set(&name.value[..3]).unwrap();
+ *
+ * assert_eq!(unsafe { name.ptr.as_ref_unchecked() }, b"Fer");
+ * # }
+ * ```
+ *
+ * # Safety
+ *
+ * The same rules as `RelPtr` apply
+ */
+#[repr(transparent)]
+pub struct RelConst<T: ?Sized + MetaData, I: Delta = isize>(pub(crate) RelPtr<T, I>);
+
+/**
+ * A relative pointer that can be used to read and write its pointee, like `&mut T`
+ *
+ * `RelMut` has the accessors for shared and mutable references, and it is `Send` when `T` is
+ * `Send`, and `Sync` when `T` is `Sync`, like `&mut T`. It is not `Copy`, so that it can't be
+ * duplicated by accident, and it can be borrowed as a `RelConst` with `RelMut::as_const`.
+ *
+ * `RelMut` has the same layout as `RelPtr`, and `RelMut::from_rel_ptr_mut` gives a
+ * `RelMut` view of a `RelPtr` in place.
+ *
+ * Like `RelPtr`, it is invariant in `T`.
+ *
+ * # Threads
+ *
+ * `RelPtr` is neither `Send` nor `Sync`, so a type that holds one can't cross threads without
+ * an `unsafe impl`. `RelMut` and `RelConst` are the opt-in: they are `Send` and `Sync` when
+ * `&mut T` and `&T` are, so a type that uses them for its relative pointers gets the right
+ * `Send` and `Sync` impls from the compiler, based on what it points to.
+ *
+ * This is sound because a relative pointer can only be resolved where it is, so it can only be
+ * used on another thread if it was sent there, along with the value that holds it. For a
+ * self-referential type the target moves with it, and is sent or shared along with it, like
+ * the fields of any other type. If the target is outside of the value, then it must be valid to
+ * access from the other thread, which is part of the safety contract of the accessors.
+ *
+ * ```rust
+ * # fn main() {
+ * use rel_ptr::RelMut;
+ *
+ * struct Words {
+ *     text: [u8; 16],
+ *     last: RelMut<[u8], i8>,
+ * }
+ *
+ * let mut words = Box::new(Words { text: *b"sent to a worker", last: RelMut::null() });
+ * words.last.set(&mut words.text[10..]).unwrap();
+ *
+ * let last = std::thread::spawn(move || unsafe { words.last.as_ref_unchecked().to_vec() });
+ *
+ * assert_eq!(last.join().unwrap(), b"worker");
+ * # }
+ * ```
+ *
+ * Types that hold a `RelPtr` are not `Send`
+ *
+ * ```compile_fail
+ * # fn send<T: Send>() {}
+ * struct Words {
+ *     text: [u8; 16],
+ *     last: rel_ptr::RelPtr<[u8], i8>,
+ * }
+ *
+ * send::<Words>();
+ * ```
+ *
+ * and `RelMut` is only `Send` if the pointee is, like `&mut T`
+ *
+ * ```compile_fail
+ * # fn send<T: Send>() {}
+ * send::<rel_ptr::RelMut<std::rc::Rc<u8>>>();
+ * ```
+ *
+ * and only `Sync` if the pointee is, because it can be resolved through a shared reference
+ *
+ * ```compile_fail
+ * # fn sync<T: Sync>() {}
+ * sync::<rel_ptr::RelMut<std::cell::Cell<u8>>>();
+ * ```
+ *
+ * and `RelConst` is only `Send` if the pointee is `Sync`, like `&T`
+ *
+ * ```compile_fail
+ * # fn send<T: Send>() {}
+ * send::<rel_ptr::RelConst<std::cell::Cell<u8>>>();
+ * ```
+ *
+ * # Safety
+ *
+ * The same rules as `RelPtr` apply
+ */
+#[repr(transparent)]
+pub struct RelMut<T: ?Sized + MetaData, I: Delta = isize>(pub(crate) RelPtr<T, I>);
+
+unsafe impl<T: ?Sized + MetaData + Sync, I: Delta + Send> Send for RelConst<T, I> where T::Data: Send {}
+unsafe impl<T: ?Sized + MetaData + Sync, I: Delta + Sync> Sync for RelConst<T, I> where T::Data: Sync {}
+
+unsafe impl<T: ?Sized + MetaData + Send, I: Delta + Send> Send for RelMut<T, I> where T::Data: Send {}
+unsafe impl<T: ?Sized + MetaData + Sync, I: Delta + Sync> Sync for RelMut<T, I> where T::Data: Sync {}
+
+impl<T: ?Sized + MetaData, I: Delta> Copy for RelConst<T, I> {}
+impl<T: ?Sized + MetaData, I: Delta> Clone for RelConst<T, I> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized + MetaData, I: Delta> From<RelPtr<T, I>> for RelConst<T, I> {
+    #[inline(always)]
+    fn from(ptr: RelPtr<T, I>) -> Self {
+        Self(ptr)
+    }
+}
+
+impl<T: ?Sized + MetaData, I: Delta> From<RelPtr<T, I>> for RelMut<T, I> {
+    #[inline(always)]
+    fn from(ptr: RelPtr<T, I>) -> Self {
+        Self(ptr)
+    }
+}
+
+impl<T: ?Sized + MetaData, I: Delta> From<RelMut<T, I>> for RelConst<T, I> {
+    #[inline(always)]
+    fn from(ptr: RelMut<T, I>) -> Self {
+        Self(ptr.0)
+    }
+}
+
+impl<T: ?Sized + MetaData, I: Nullable> RelConst<T, I> {
+    /// A null relative pointer has an offset of 0, (points to itself)
+    #[inline(always)]
+    pub fn null() -> Self {
+        Self(RelPtr::null())
+    }
+
+    /// Check if the relative pointer is null
+    #[inline(always)]
+    pub fn is_null(&self) -> bool {
+        self.0.is_null()
+    }
+
+    /**
+     * Gets a reference from the relative pointer,
+     * if the relative pointer is null, then `None` is
+     * returned
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_ref`
+     */
+    #[inline]
+    pub unsafe fn as_ref(&self) -> Option<&T> {
+        self.0.as_ref()
+    }
+}
+
+impl<T: ?Sized + MetaData, I: Delta> RelConst<T, I> {
+    /**
+     * View a relative pointer in place as a `RelConst`
+     *
+     * The view is at the same address, so it points to the same target
+     */
+    #[inline(always)]
+    pub fn from_rel_ptr(ptr: &RelPtr<T, I>) -> &Self {
+        unsafe { &*(ptr as *const RelPtr<T, I> as *const Self) }
+    }
+
+    /// The underlying relative pointer, at the same address
+    #[inline(always)]
+    pub fn as_rel_ptr(&self) -> &RelPtr<T, I> {
+        &self.0
+    }
+
+    /**
+     * Set the offset of the relative pointer,
+     * if the offset cannot be calculated using the given
+     * `Delta`, then `Err` will be returned, and there will be
+     * **no** change to the offset
+     *
+     * Unlike `RelPtr::set`, this only needs a shared reference to the pointee
+     */
+    #[inline]
+    pub fn set(&mut self, value: &T) -> Result<(), I::Error> {
+        (self.0).0 = I::sub(value as *const T as _, self as *mut Self as _)?;
+        (self.0).1 = MaybeUninit::new(T::data(value));
+
+        Ok(())
+    }
+
+    /**
+     * Gets a reference from the relative pointer
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_ref_unchecked`
+     */
+    #[inline]
+    pub unsafe fn as_ref_unchecked(&self) -> &T {
+        self.0.as_ref_unchecked()
+    }
+}
+
+impl<T: ?Sized + MetaData, I: Nullable> RelMut<T, I> {
+    /// A null relative pointer has an offset of 0, (points to itself)
+    #[inline(always)]
+    pub fn null() -> Self {
+        Self(RelPtr::null())
+    }
+
+    /// Check if the relative pointer is null
+    #[inline(always)]
+    pub fn is_null(&self) -> bool {
+        self.0.is_null()
+    }
+
+    /**
+     * Gets a reference from the relative pointer,
+     * if the relative pointer is null, then `None` is
+     * returned
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_ref`
+     */
+    #[inline]
+    pub unsafe fn as_ref(&self) -> Option<&T> {
+        self.0.as_ref()
+    }
+
+    /**
+     * Gets a mutable reference from the relative pointer,
+     * if the relative pointer is null, then `None` is
+     * returned
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_mut`
+     */
+    #[inline]
+    pub unsafe fn as_mut(&mut self) -> Option<&mut T> {
+        self.0.as_mut()
+    }
+}
+
+impl<T: ?Sized + MetaData, I: Delta> RelMut<T, I> {
+    /**
+     * View a relative pointer in place as a `RelMut`
+     *
+     * The view is at the same address, so it points to the same target
+     */
+    #[inline(always)]
+    pub fn from_rel_ptr_mut(ptr: &mut RelPtr<T, I>) -> &mut Self {
+        unsafe { &mut *(ptr as *mut RelPtr<T, I> as *mut Self) }
+    }
+
+    /// Borrow the relative pointer in place as a `RelConst`, like reborrowing `&mut T` as `&T`
+    #[inline(always)]
+    pub fn as_const(&self) -> &RelConst<T, I> {
+        RelConst::from_rel_ptr(&self.0)
+    }
+
+    /// The underlying relative pointer, at the same address
+    #[inline(always)]
+    pub fn as_rel_ptr(&self) -> &RelPtr<T, I> {
+        &self.0
+    }
+
+    /// The underlying relative pointer mutably, at the same address
+    #[inline(always)]
+    pub fn as_rel_ptr_mut(&mut self) -> &mut RelPtr<T, I> {
+        &mut self.0
+    }
+
+    /**
+     * Set the offset of the relative pointer,
+     * if the offset cannot be calculated using the given
+     * `Delta`, then `Err` will be returned, and there will be
+     * **no** change to the offset
+     */
+    #[inline]
+    pub fn set(&mut self, value: &mut T) -> Result<(), I::Error> {
+        self.0.set(value)
+    }
+
+    /**
+     * Gets a reference from the relative pointer
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_ref_unchecked`
+     */
+    #[inline]
+    pub unsafe fn as_ref_unchecked(&self) -> &T {
+        self.0.as_ref_unchecked()
+    }
+
+    /**
+     * Gets a mutable reference from the relative pointer
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_mut_unchecked`
+     */
+    #[inline]
+    pub unsafe fn as_mut_unchecked(&mut self) -> &mut T {
+        self.0.as_mut_unchecked()
+    }
+}
+
+impl<T: ?Sized + MetaData, I: Delta> RelTrace for RelConst<T, I> where RelPtr<T, I>: RelTrace {
+    #[inline]
+    fn trace<V: RelVisitor + ?Sized>(&self, visitor: &mut V) {
+        self.0.trace(visitor)
+    }
+
+    #[inline]
+    fn trace_mut<V: RelVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        self.0.trace_mut(visitor)
+    }
+}
+
+impl<T: ?Sized + MetaData, I: Delta> RelTrace for RelMut<T, I> where RelPtr<T, I>: RelTrace {
+    #[inline]
+    fn trace<V: RelVisitor + ?Sized>(&self, visitor: &mut V) {
+        self.0.trace(visitor)
+    }
+
+    #[inline]
+    fn trace_mut<V: RelVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        self.0.trace_mut(visitor)
+    }
+}
@@ -0,0 +1,164 @@
+use super::{RelPtr, MetaData, Nullable, IntegerDelta, IntegerDeltaError, IntegerDeltaErrorImpl};
+
+/// Wrap `del` into the range of `I`, like the `wrapping_*` integer operations
+fn wrap<I: IntegerDelta + Nullable>(del: isize) -> I {
+    let bits = (std::mem::size_of::<isize>() * 8) as u32;
+
+    let del = if I::BITS < bits {
+        let shift = bits - I::BITS;
+        (del << shift) >> shift
+    } else {
+        del
+    };
+
+    I::from_isize(del).expect("a wrapped offset always fits in its `IntegerDelta`")
+}
+
+/// The offset in bytes of `count` elements of size `size`, checked for overflow
+fn checked_bytes(count: isize, size: usize) -> Result<isize, IntegerDeltaError> {
+    count.checked_mul(size as isize)
+        .ok_or(IntegerDeltaError(IntegerDeltaErrorImpl::Overflow))
+}
+
+impl<T: ?Sized + MetaData, I: IntegerDelta> RelPtr<T, I> {
+    /**
+     * Move the target of the relative pointer by `bytes` bytes,
+     * see `RelPtr::offset_by`
+     */
+    #[inline]
+    pub fn byte_offset_by(&mut self, bytes: isize) -> Result<(), IntegerDeltaError> {
+        let del = self.0.try_to_isize()?.checked_add(bytes)
+            .ok_or(IntegerDeltaError(IntegerDeltaErrorImpl::Overflow))?;
+
+        self.0 = I::from_isize(del)?;
+
+        Ok(())
+    }
+}
+
+impl<T: ?Sized + MetaData, I: IntegerDelta + Nullable> RelPtr<T, I> {
+    /**
+     * Move the target of the relative pointer by `bytes` bytes,
+     * see `RelPtr::wrapping_offset_by`
+     */
+    #[inline]
+    pub fn wrapping_byte_offset_by(&mut self, bytes: isize) {
+        self.0 = wrap(self.0.to_isize().wrapping_add(bytes));
+    }
+}
+
+impl<T, I: IntegerDelta> RelPtr<T, I> {
+    /**
+     * Move the target of the relative pointer by `count` elements of `T`, like `<*const T>::offset`,
+     * without resolving the relative pointer
+     *
+     * This only changes the stored offset. If the new offset can't be stored in `I`
+     * (or overflows `isize`, or the old one doesn't fit in `isize`), then `Err` is returned
+     * and the relative pointer is not changed.
+     *
+     * ```rust
+     * # fn main() {
+     * use rel_ptr::RelPtr;
+     *
+     * let mut data = ([1_u32, 2, 3], RelPtr::<u32, i8>::null());
+     *
+     * data.1.set(&mut data.0[0]).unwrap();
+     * data.1.offset_by(2).unwrap();
+     *
+     * assert_eq!(unsafe { *data.1.as_ref_unchecked() }, 3);
+     * assert!(data.1.offset_by(100).is_err());
+     * # }
+     * ```
+     */
+    #[inline]
+    pub fn offset_by(&mut self, count: isize) -> Result<(), IntegerDeltaError> {
+        self.byte_offset_by(checked_bytes(count, std::mem::size_of::<T>())?)
+    }
+}
+
+impl<T, I: IntegerDelta + Nullable> RelPtr<T, I> {
+    /**
+     * Move the target of the relative pointer by `count` elements of `T`,
+     * wrapping around if the offset doesn't fit in `I`, like `<*const T>::wrapping_offset`
+     */
+    #[inline]
+    pub fn wrapping_offset_by(&mut self, count: isize) {
+        self.wrapping_byte_offset_by(count.wrapping_mul(std::mem::size_of::<T>() as isize))
+    }
+}
+
+impl<T, I: IntegerDelta> RelPtr<[T], I> {
+    /**
+     * Remove the first `n` elements from the slice, without resolving the relative pointer
+     *
+     * This is useful for cursors over relocatable arrays. A relative pointer with an offset of 0
+     * is null, and is treated as an empty slice. If the new offset can't be stored in `I`
+     * (or overflows `isize`), then `Err` is returned and the relative pointer is not changed.
+     *
+     * ```rust
+     * # fn main() {
+     * use rel_ptr::RelPtr;
+     *
+     * let mut data = ([1_u32, 2, 3], RelPtr::<[u32], i8>::null());
+     *
+     * data.1.set(&mut data.0).unwrap();
+     *
+     * unsafe {
+     *     data.1.advance(2).unwrap();
+     *     assert_eq!(data.1.as_ref_unchecked(), [3]);
+     * }
+     * # }
+     * ```
+     *
+     * # Panics
+     *
+     * If `n` is larger than the length of the slice
+     *
+     * # Safety
+     *
+     * The relative pointer must have been successfully set, or be null
+     */
+    pub unsafe fn advance(&mut self, n: usize) -> Result<(), IntegerDeltaError> {
+        let len = self.advance_len(n);
+
+        if n != 0 {
+            self.byte_offset_by(checked_bytes(n as isize, std::mem::size_of::<T>())?)?;
+            self.1 = std::mem::MaybeUninit::new(len);
+        }
+
+        Ok(())
+    }
+
+    /// The length after advancing by `n`
+    unsafe fn advance_len(&self, n: usize) -> usize {
+        let len = if matches!(self.0.try_to_isize(), Ok(0)) { 0 } else { self.metadata() };
+
+        assert!(n <= len, "tried to advance by {} elements, but the length is {}", n, len);
+
+        len - n
+    }
+}
+
+impl<T, I: IntegerDelta + Nullable> RelPtr<[T], I> {
+    /**
+     * Remove the first `n` elements from the slice, wrapping the offset
+     * around if it doesn't fit in `I`, see `RelPtr::advance`
+     *
+     * # Panics
+     *
+     * If `n` is larger than the length of the slice
+     *
+     * # Safety
+     *
+     * The relative pointer must have been successfully set, or be null
+     */
+    pub unsafe fn wrapping_advance(&mut self, n: usize) {
+        let len = self.advance_len(n);
+
+        if n != 0 {
+            self.wrapping_byte_offset_by((n as isize).wrapping_mul(std::mem::size_of::<T>() as isize));
+            self.1 = std::mem::MaybeUninit::new(len);
+        }
+    }
+
+}
@@ -0,0 +1,188 @@
+use super::{Delta, Nullable, IntegerDelta, IntegerDeltaError, IntegerDeltaErrorImpl};
+use crate::unreachable::{self, UncheckedOptionExt as _};
+
+/**
+ * The number of bytes needed to store `offset`,
+ * this is `1`, `2`, `4` or `8` for `i8`, `i16`, `i32` and `i64`
+ *
+ * This is what `select_delta!` uses to pick an offset type
+ */
+pub const fn smallest_delta_bytes(offset: isize) -> usize {
+    let offset = offset as i64;
+
+    if offset >= i8::MIN as i64 && offset <= i8::MAX as i64 {
+        1
+    } else if offset >= i16::MIN as i64 && offset <= i16::MAX as i64 {
+        2
+    } else if offset >= i32::MIN as i64 && offset <= i32::MAX as i64 {
+        4
+    } else {
+        8
+    }
+}
+
+/// Maps the number of bytes of an offset to an integer `Delta`,
+/// this is used by `select_delta!`
+pub struct DeltaBytes<const BYTES: usize>;
+
+/// Maps a type to an integer `Delta`, see `select_delta!`
+pub trait SelectDelta {
+    /// The selected `Delta`
+    type Delta: IntegerDelta + Nullable;
+}
+
+impl SelectDelta for DeltaBytes<1> { type Delta = i8; }
+impl SelectDelta for DeltaBytes<2> { type Delta = i16; }
+impl SelectDelta for DeltaBytes<4> { type Delta = i32; }
+impl SelectDelta for DeltaBytes<8> { type Delta = i64; }
+
+/**
+ * Selects the smallest integer `Delta` that can store the offset between
+ * two fields of a struct, at compile time
+ *
+ * The first field path is the field that holds the relative pointer, and the second is
+ * the field that it points to. The field offsets are computed from raw pointers into an
+ * uninitialized value, like `memoffset`, but in a constant, so expanding `select_delta!`
+ * requires Rust 1.65 for `const` pointer arithmetic. The rest of the crate only requires 1.51
+ *
+ * A struct can't use `select_delta!` on itself in its own fields, because its layout
+ * would depend on itself. Instead make the struct generic over the offset type, and
+ * select the offset type using the `isize` version. If the struct is `#[repr(C)]`, then
+ * using a smaller offset type can only bring the fields closer together, so the selected
+ * type is guaranteed to fit.
+ *
+ * ```rust
+ * # fn main() {
+ * use rel_ptr::{RelPtr, Delta, select_delta};
+ *
+ * #[repr(C)]
+ * struct Big<I: Delta> {
+ *     data: [u8; 1000],
+ *     value: u8,
+ *     near: RelPtr<u8, I>,
+ * }
+ *
+ * type Near = select_delta!(Big<isize>, near, value);
+ * type Far = select_delta!(Big<isize>, near, data);
+ *
+ * assert_eq!(std::mem::size_of::<Near>(), 1);
+ * assert_eq!(std::mem::size_of::<Far>(), 2);
+ *
+ * let mut big = Big::<Near> { data: [0; 1000], value: 10, near: RelPtr::null() };
+ *
+ * big.near.set(&mut big.value).unwrap();
+ * # }
+ * ```
+ */
+#[macro_export]
+macro_rules! select_delta {
+    ($type:ty, $($from:tt).+, $($to:tt).+) => {
+        <$crate::DeltaBytes<{
+            $crate::smallest_delta_bytes({
+                let value = ::core::mem::MaybeUninit::<$type>::uninit();
+                let base = value.as_ptr();
+
+                // only raw pointers to the fields are made, the uninitialized value is never read
+                #[allow(unused_unsafe)]
+                unsafe {
+                    (::core::ptr::addr_of!((*base).$($to).+) as *const u8)
+                        .offset_from(::core::ptr::addr_of!((*base).$($from).+) as *const u8)
+                }
+            })
+        }> as $crate::SelectDelta>::Delta
+    };
+}
+
+/**
+ * A `Delta` that picks the smallest integer that can store each offset
+ *
+ * In memory `Auto` is always as large as `i64` plus a tag, so this is
+ * not useful to save space directly. But it allows serialized formats to
+ * store each offset with the smallest width that fits, see `Auto::bytes`
+ *
+ * Offsets are always stored in the smallest variant that fits, so that
+ * `Auto::NULL` is the only representation of a null offset
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Auto {
+    /// An offset that fits in an `i8`
+    I8(i8),
+    /// An offset that fits in an `i16`
+    I16(i16),
+    /// An offset that fits in an `i32`
+    I32(i32),
+    /// An offset that fits in an `i64`
+    I64(i64),
+}
+
+impl Auto {
+    /// The number of bytes needed to store this offset
+    pub fn bytes(self) -> usize {
+        match self {
+            Auto::I8(_) => 1,
+            Auto::I16(_) => 2,
+            Auto::I32(_) => 4,
+            Auto::I64(_) => 8,
+        }
+    }
+}
+
+unsafe impl Delta for Auto {
+    type Error = IntegerDeltaError;
+
+    fn sub(a: *mut u8, b: *mut u8) -> Result<Self, Self::Error> {
+        let del = match isize::checked_sub(a as usize as _, b as usize as _) {
+            Some(del) => del,
+            None => return Err(IntegerDeltaError(IntegerDeltaErrorImpl::Sub(a as usize, b as usize)))
+        };
+
+        Self::from_isize(del)
+    }
+
+    unsafe fn sub_unchecked(a: *mut u8, b: *mut u8) -> Self {
+        let del = isize::checked_sub(a as usize as _, b as usize as _).unchecked_unwrap(unreachable::OVERFLOW_SUB);
+
+        Self::from_isize(del).ok().unchecked_unwrap(unreachable::OVERFLOW_SUB)
+    }
+
+    unsafe fn add(self, a: *const u8) -> *mut u8 {
+        <*const u8>::offset(a, self.to_isize()) as *mut u8
+    }
+
+    #[inline]
+    fn byte_offset(self) -> Option<isize> {
+        self.try_to_isize().ok()
+    }
+}
+
+unsafe impl IntegerDelta for Auto {
+    const BITS: u32 = 64;
+
+    #[inline]
+    fn to_isize(self) -> isize {
+        self.try_to_isize().expect("the offset doesn't fit in an `isize`")
+    }
+
+    #[inline]
+    fn try_to_isize(self) -> Result<isize, IntegerDeltaError> {
+        Ok(match self {
+            Auto::I8(del) => del as isize,
+            Auto::I16(del) => del as isize,
+            Auto::I32(del) => del as isize,
+            Auto::I64(del) => del.try_to_isize()?,
+        })
+    }
+
+    fn from_isize(del: isize) -> Result<Self, IntegerDeltaError> {
+        Ok(match smallest_delta_bytes(del) {
+            1 => Auto::I8(del as i8),
+            2 => Auto::I16(del as i16),
+            4 => Auto::I32(del as i32),
+            _ => Auto::I64(i64::from_isize(del)?),
+        })
+    }
+}
+
+impl Nullable for Auto {
+    const NULL: Self = Auto::I8(0);
+}
@@ -0,0 +1,97 @@
+//! Prints an annotated hexdump and a Graphviz DOT graph of a relocatable image
+//!
+//! ```text
+//! rel-ptr-inspect [--dot <output>] <image> <root offset> <schema> [root type]
+//! ```
+//!
+//! The root type defaults to the first struct in the schema, see `rel_ptr::inspect`
+//! for the schema syntax. The DOT graph is written to `<output>` if it is given,
+//! otherwise it is printed after the hexdump. The exit code is 2 if any problems
+//! were found in the image.
+
+use rel_ptr::inspect::{inspect, Schema};
+
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::process::exit;
+
+const USAGE: &str = "usage: rel-ptr-inspect [--dot <output>] <image> <root offset> <schema> [root type]";
+
+fn parse_offset(text: &str) -> Result<usize, Box<dyn Error>> {
+    let offset = match text.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => text.parse(),
+    };
+
+    offset.map_err(|_| format!("`{}` is not a valid root offset", text).into())
+}
+
+fn run() -> Result<bool, Box<dyn Error>> {
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let mut dot = None;
+
+    if let Some(i) = args.iter().position(|arg| arg == "--dot") {
+        if i + 1 == args.len() {
+            return Err(USAGE.into());
+        }
+
+        dot = Some(args.remove(i + 1));
+        args.remove(i);
+    }
+
+    if args.len() < 3 || args.len() > 4 {
+        return Err(USAGE.into());
+    }
+
+    let image = fs::read(&args[0])?;
+    let root = parse_offset(&args[1])?;
+    let schema = Schema::parse(&fs::read_to_string(&args[2])?)?;
+    let root_type = match args.get(3) {
+        Some(name) => name.clone(),
+        None => schema.structs().first().ok_or("the schema is empty")?.name.clone(),
+    };
+
+    let report = inspect(&image, root, &schema, &root_type)?;
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    report.write_hexdump(&image, &mut out)?;
+
+    if !report.is_ok() {
+        writeln!(out)?;
+        writeln!(out, "{} problem(s) found:", report.issues.len())?;
+
+        for issue in &report.issues {
+            writeln!(out, "  {}", issue.message)?;
+        }
+    }
+
+    match dot {
+        Some(path) => {
+            let mut file = BufWriter::new(File::create(path)?);
+            report.write_dot(&mut file)?;
+            file.flush()?;
+        },
+        None => {
+            writeln!(out)?;
+            report.write_dot(&mut out)?;
+        },
+    }
+
+    out.flush()?;
+
+    Ok(report.is_ok())
+}
+
+fn main() {
+    match run() {
+        Ok(true) => (),
+        Ok(false) => exit(2),
+        Err(err) => {
+            eprintln!("rel-ptr-inspect: {}", err);
+            exit(1);
+        },
+    }
+}
@@ -0,0 +1,179 @@
+use super::{RelPtr, TaggedRelPtr, MetaData, Nullable, IntegerDelta, Ptr};
+
+use std::ptr::NonNull;
+use std::convert::Infallible;
+use std::marker::PhantomData;
+
+/**
+ * A relative pointer that can be followed one hop to its pointee
+ *
+ * This is what allows chains of relative pointers, like
+ * `RelPtr<RelPtr<[u8], i16>, i32>`, to be resolved in one call,
+ * see `RelPtr::resolve_chain`
+ */
+pub trait RelDeref {
+    /// The type that is pointed to
+    type Target: ?Sized;
+
+    /**
+     * Converts the relative pointer into a NonNull pointer,
+     * if the relative pointer is null, then `None` is returned
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_non_null`
+     */
+    unsafe fn rel_deref(&self) -> Ptr<Self::Target>;
+}
+
+impl<T: ?Sized + MetaData, I: Nullable> RelDeref for RelPtr<T, I> {
+    type Target = T;
+
+    #[inline]
+    unsafe fn rel_deref(&self) -> Ptr<T> {
+        if self.is_null() {
+            None
+        } else {
+            NonNull::new(self.as_raw_unchecked_impl() as *mut T)
+        }
+    }
+}
+
+impl<T: ?Sized + MetaData, I: IntegerDelta + Nullable, const BITS: u32> RelDeref for TaggedRelPtr<T, I, BITS> {
+    type Target = T;
+
+    #[inline]
+    unsafe fn rel_deref(&self) -> Ptr<T> {
+        if self.is_null() {
+            None
+        } else {
+            NonNull::new(self.as_raw_unchecked_impl() as *mut T)
+        }
+    }
+}
+
+/// A single hop while resolving a chain of relative pointers,
+/// this is passed to the validation hook of `RelPtr::resolve_chain_with`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hop {
+    /// How many hops came before this one
+    pub depth: usize,
+
+    /// The address of the relative pointer
+    pub from: *const u8,
+
+    /// The address that the relative pointer resolved to
+    pub to: *const u8,
+}
+
+/// The path of a chain that ends after following one more relative pointer,
+/// see `RelChain`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Here;
+
+/// The path of a chain that follows one relative pointer and then continues along `N`,
+/// see `RelChain`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct There<N>(PhantomData<N>);
+
+/**
+ * A chain of relative pointers that ends at a `U`
+ *
+ * `Path` is the number of hops, written as `There<There<..<Here>>>`,
+ * it is always inferred from `U`. So `RelPtr<RelPtr<[u8], i16>, i32>`
+ * is a `RelChain<[u8], There<Here>>` and a `RelChain<RelPtr<[u8], i16>, Here>`
+ */
+pub trait RelChain<U: ?Sized, Path> {
+    /**
+     * Follow the chain, calling `validate` for each hop,
+     * `depth` is the number of hops that came before this one
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::resolve_chain_with`
+     */
+    unsafe fn resolve_chain_from<E, F>(&self, depth: usize, validate: &mut F) -> Result<Option<NonNull<U>>, E>
+    where
+        F: FnMut(Hop) -> Result<(), E>;
+}
+
+impl<P: RelDeref> RelChain<P::Target, Here> for P {
+    unsafe fn resolve_chain_from<E, F>(&self, depth: usize, validate: &mut F) -> Result<Option<NonNull<P::Target>>, E>
+    where
+        F: FnMut(Hop) -> Result<(), E>
+    {
+        let target = match self.rel_deref() {
+            Some(target) => target,
+            None => return Ok(None),
+        };
+
+        validate(Hop {
+            depth,
+            from: self as *const Self as *const u8,
+            to: target.as_ptr() as *const u8,
+        })?;
+
+        Ok(Some(target))
+    }
+}
+
+impl<U: ?Sized, N, P: RelDeref> RelChain<U, There<N>> for P
+where
+    P::Target: RelChain<U, N>
+{
+    unsafe fn resolve_chain_from<E, F>(&self, depth: usize, validate: &mut F) -> Result<Option<NonNull<U>>, E>
+    where
+        F: FnMut(Hop) -> Result<(), E>
+    {
+        match RelChain::<P::Target, Here>::resolve_chain_from(self, depth, validate)? {
+            Some(next) => RelChain::<U, N>::resolve_chain_from(next.as_ref(), depth + 1, validate),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T: ?Sized + MetaData, I: Nullable> RelPtr<T, I> {
+    /**
+     * Follow this relative pointer, and every relative pointer after it,
+     * until a `U` is reached, if any relative pointer is null, then `None` is returned
+     *
+     * The number of hops is inferred from `U`, so resolving
+     * `RelPtr<RelPtr<RelPtr<T>>>` to a `T` follows three hops,
+     * and resolving it to a `RelPtr<T>` follows two hops.
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_ref` for each hop
+     */
+    #[inline]
+    pub unsafe fn resolve_chain<U: ?Sized, Path>(&self) -> Option<&U>
+    where
+        Self: RelChain<U, Path>
+    {
+        match self.resolve_chain_with(|_| Ok::<(), Infallible>(())) {
+            Ok(target) => target,
+            Err(never) => match never {},
+        }
+    }
+
+    /**
+     * Follow this relative pointer, and every relative pointer after it,
+     * until a `U` is reached, if any relative pointer is null, then `Ok(None)` is returned
+     *
+     * `validate` is called for each hop, before anything is read from
+     * the resolved address, so it can be used to check that the target
+     * is in bounds and aligned. If it returns `Err` then resolution stops
+     * and the error is returned.
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_ref` for each hop that passes validation
+     */
+    pub unsafe fn resolve_chain_with<U: ?Sized, Path, E, F>(&self, mut validate: F) -> Result<Option<&U>, E>
+    where
+        Self: RelChain<U, Path>,
+        F: FnMut(Hop) -> Result<(), E>
+    {
+        Ok(self.resolve_chain_from(0, &mut validate)?.map(|target| &*target.as_ptr()))
+    }
+}
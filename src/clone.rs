@@ -0,0 +1,209 @@
+use super::{RelPtr, MetaData, IntegerDelta, IntegerDeltaError};
+
+use core::mem::MaybeUninit;
+
+/**
+ * A type that owns a single block of memory outside of itself, like the buffer of a `String`
+ *
+ * This is used by `RelClone` to find out where relative pointers into the
+ * owned memory should point to in the clone
+ *
+ * # Safety
+ *
+ * `owned_memory` must return the start and length of the memory owned by `self`,
+ * and a clone of `self` must have the same layout in its own owned memory
+ */
+pub unsafe trait OwnedMemory {
+    /// The start and length in bytes of the memory owned by `self`
+    fn owned_memory(&self) -> (*const u8, usize);
+}
+
+#[cfg(not(feature = "no_std"))]
+unsafe impl OwnedMemory for String {
+    #[inline]
+    fn owned_memory(&self) -> (*const u8, usize) {
+        (self.as_ptr(), self.len())
+    }
+}
+
+#[cfg(not(feature = "no_std"))]
+unsafe impl<T> OwnedMemory for Vec<T> {
+    #[inline]
+    fn owned_memory(&self) -> (*const u8, usize) {
+        (self.as_ptr() as *const u8, std::mem::size_of_val(&self[..]))
+    }
+}
+
+#[cfg(not(feature = "no_std"))]
+unsafe impl<T: ?Sized> OwnedMemory for Box<T> {
+    #[inline]
+    fn owned_memory(&self) -> (*const u8, usize) {
+        (&**self as *const T as *const u8, std::mem::size_of_val(&**self))
+    }
+}
+
+/**
+ * A block of memory in the original value, and where it is in the clone
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct CloneRegion {
+    old: usize,
+    new: usize,
+    len: usize,
+}
+
+impl CloneRegion {
+    /// The memory of the value itself
+    #[inline]
+    pub fn inline<T>(old: &T, new: &T) -> Self {
+        Self {
+            old: old as *const T as usize,
+            new: new as *const T as usize,
+            len: std::mem::size_of::<T>(),
+        }
+    }
+
+    /// The memory owned by a field of the value
+    #[inline]
+    pub fn owned<T: ?Sized + OwnedMemory>(old: &T, new: &T) -> Self {
+        let (old, len) = old.owned_memory();
+        let (new, _) = new.owned_memory();
+
+        Self { old: old as usize, new: new as usize, len }
+    }
+
+    /// Where `addr` is in the clone, if it is inside of this region
+    #[inline]
+    fn map(&self, addr: usize) -> Option<usize> {
+        let pos = addr.wrapping_sub(self.old);
+
+        if pos < self.len {
+            Some(self.new.wrapping_add(pos))
+        } else {
+            None
+        }
+    }
+}
+
+/**
+ * Set `new` to point to the equivalent of the pointee of `old` in the clone
+ *
+ * The first of the `regions` that contains the pointee of `old` is used,
+ * so the inline region should come first. If no region contains the pointee,
+ * then it is not owned by the value, and `new` will point to the same pointee as `old`.
+ *
+ * This is used by `impl_rel_clone!`
+ */
+pub fn rel_clone_ptr<T: ?Sized + MetaData, I: IntegerDelta>(
+    regions: &[CloneRegion],
+    old: &RelPtr<T, I>,
+    new: &mut RelPtr<T, I>,
+) -> Result<(), IntegerDeltaError> {
+    let target = (old as *const RelPtr<T, I> as usize).wrapping_add(old.0.to_isize() as usize);
+    let target = regions.iter()
+        .find_map(|region| region.map(target))
+        .unwrap_or(target);
+
+    new.0 = I::sub(target as *mut u8, new as *mut RelPtr<T, I> as *mut u8)?;
+    new.1 = old.1;
+
+    Ok(())
+}
+
+/**
+ * Clone a self-referential type, and fix up the relative pointers in the clone
+ *
+ * `RelPtr` is `Copy`, so `Clone` only copies the offsets. This is fine for relative
+ * pointers to other fields of the value, but wrong for relative pointers into memory
+ * that the value owns, like the buffer of a `String`. `RelClone` clones the value
+ * and then sets each relative pointer to the equivalent pointee in the clone.
+ * Relative pointers to memory that the value doesn't own keep pointing to the same pointee.
+ *
+ * Relative pointers into owned memory depend on where the value is, so the clone
+ * is written into a place, use `impl_rel_clone!` to implement it.
+ */
+pub trait RelClone: Sized {
+    /**
+     * Clone `self` into `dest`, and fix up the relative pointers in the clone
+     *
+     * If any relative pointer can't be set, then the clone is dropped and `Err` is returned
+     */
+    fn rel_clone_into<'a>(&self, dest: &'a mut MaybeUninit<Self>) -> Result<&'a mut Self, IntegerDeltaError>;
+
+    /// Clone `self` into a new `Box`, see `rel_clone_into`
+    #[cfg(not(feature = "no_std"))]
+    fn rel_clone_boxed(&self) -> Result<Box<Self>, IntegerDeltaError> {
+        let mut dest = Box::new(MaybeUninit::uninit());
+
+        self.rel_clone_into(&mut dest)?;
+
+        unsafe { Ok(Box::from_raw(Box::into_raw(dest) as *mut Self)) }
+    }
+}
+
+/**
+ * Implement `RelClone` for a struct that implements `Clone`
+ *
+ * List the relative pointers to fix up in `ptrs`, and the fields that own memory
+ * outside of the struct (with `OwnedMemory`) in `owns`. Generic parameters go in
+ * square brackets after `impl`, like `impl_rel_trace!`.
+ *
+ * ```rust
+ * # #[cfg(feature = "no_std")] fn main() {}
+ * # #[cfg(not(feature = "no_std"))] fn main() {
+ * use rel_ptr::{RelPtr, RelClone, impl_rel_clone};
+ *
+ * #[derive(Clone)]
+ * struct Words {
+ *     text: String,
+ *     first: RelPtr<str, i64>,
+ * }
+ *
+ * impl_rel_clone!(Words { ptrs: [first], owns: [text] });
+ *
+ * let mut words = Box::new(Words { text: "hello world".to_string(), first: RelPtr::null() });
+ * let first = &mut words.text[..5] as *mut str;
+ * words.first.set(unsafe { &mut *first }).unwrap();
+ *
+ * let copy = words.rel_clone_boxed().unwrap();
+ * words.text.make_ascii_uppercase();
+ *
+ * assert_eq!(unsafe { copy.first.as_ref_unchecked() }, "hello");
+ * # }
+ * ```
+ */
+#[macro_export]
+macro_rules! impl_rel_clone {
+    (impl [$($gen:tt)*] $type:ty { ptrs: [$($ptr:tt),* $(,)?], owns: [$($owns:tt),* $(,)?] $(,)? }) => {
+        impl<$($gen)*> $crate::RelClone for $type {
+            fn rel_clone_into<'a>(
+                &self,
+                dest: &'a mut ::core::mem::MaybeUninit<Self>,
+            ) -> ::core::result::Result<&'a mut Self, $crate::IntegerDeltaError> {
+                *dest = ::core::mem::MaybeUninit::new(::core::clone::Clone::clone(self));
+                let clone = unsafe { &mut *dest.as_mut_ptr() };
+
+                let regions = [
+                    $crate::CloneRegion::inline(self, clone),
+                    $($crate::CloneRegion::owned(&self.$owns, &clone.$owns),)*
+                ];
+
+                let result = (|| {
+                    $($crate::rel_clone_ptr(&regions, &self.$ptr, &mut clone.$ptr)?;)*
+                    Ok(())
+                })();
+
+                match result {
+                    Ok(()) => Ok(clone),
+                    Err(err) => {
+                        unsafe { ::core::ptr::drop_in_place(clone) };
+                        Err(err)
+                    }
+                }
+            }
+        }
+    };
+    ($type:ty { $($rest:tt)* }) => {
+        $crate::impl_rel_clone!(impl[] $type { $($rest)* });
+    };
+}
@@ -0,0 +1,218 @@
+use super::{RelPtr, MetaData, Delta, IntegerDelta, IntegerDeltaError, IntegerDeltaErrorImpl, Auto};
+
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use core::num::*;
+
+impl<T: ?Sized + MetaData, I: IntegerDelta> RelPtr<T, I> {
+    /**
+     * Convert the offset of the relative pointer to another `IntegerDelta`,
+     * keeping the metadata
+     *
+     * The offset is kept as it is, so the new relative pointer only points to the
+     * same target if it is in the same place as `self`. To copy a relative pointer
+     * to another place, use `RelPtr::copy_to`.
+     *
+     * If the offset can't be stored in `J`, or doesn't fit in an `isize` (which can happen
+     * for 128-bit offsets), then `Err` is returned. The `From` and `TryFrom`
+     * conversions between the built-in offset types use this.
+     *
+     * ```rust
+     * # fn main() {
+     * use rel_ptr::{RelPtr, IntegerDeltaErrorKind};
+     * use std::convert::TryFrom;
+     *
+     * let ptr = RelPtr::<u8, i32>::from(-100);
+     *
+     * assert_eq!(ptr.cast_delta::<i8>().unwrap().offset(), -100);
+     * assert_eq!(RelPtr::<u8, i64>::from(ptr).offset(), -100);
+     *
+     * let err = RelPtr::<u8, i32>::from(1000).cast_delta::<i8>().unwrap_err();
+     * assert_eq!(err.kind(), IntegerDeltaErrorKind::Conversion);
+     * assert!(RelPtr::<u8, i8>::try_from(RelPtr::<u8, i32>::from(1000)).is_err());
+     * # }
+     * ```
+     */
+    #[inline]
+    pub fn cast_delta<J: IntegerDelta>(self) -> Result<RelPtr<T, J>, IntegerDeltaError> {
+        Ok(RelPtr(J::from_isize(self.0.try_to_isize()?)?, self.1, PhantomData))
+    }
+}
+
+/**
+ * A type that can be unsized to `U`, like `CoerceUnsized` for normal pointers,
+ * see `RelPtr::unsize`
+ *
+ * This is implemented for arrays, which can be unsized to slices. Trait objects
+ * can implement it for each type that implements the trait, by taking the vtable
+ * from a dangling pointer to `Self` that was unsized to `U`.
+ *
+ * # Safety
+ *
+ * A pointer to a `Self` composed with `unsize_data` with `MetaData::compose`
+ * must be a valid pointer to a `U`, for the same value
+ */
+pub unsafe trait Unsize<U: ?Sized + MetaData> {
+    /// The metadata of `U` for a value of type `Self`
+    fn unsize_data() -> U::Data;
+}
+
+unsafe impl<T, const N: usize> Unsize<[T]> for [T; N] {
+    #[inline]
+    fn unsize_data() -> usize {
+        N
+    }
+}
+
+impl<T: ?Sized + MetaData, I: Delta> RelPtr<T, I> {
+    /**
+     * Cast the relative pointer to point to a `U`, like `<*const T>::cast`
+     *
+     * This keeps the offset, and drops the metadata, like `RelPtr::cast_delta`.
+     * It is safe, because using the new relative pointer is just as unsafe as using a raw
+     * pointer to a `U` at the same address.
+     *
+     * ```rust
+     * # fn main() {
+     * use rel_ptr::RelPtr;
+     *
+     * let mut data = ([1_u16, 2, 3], RelPtr::<[u16], i8>::null());
+     * data.1.set(&mut data.0[1..]).unwrap();
+     *
+     * let first = data.1.cast::<u16>();
+     *
+     * assert_eq!(first.offset(), data.1.offset());
+     * # }
+     * ```
+     */
+    #[inline]
+    pub fn cast<U>(self) -> RelPtr<U, I> {
+        RelPtr(self.0, MaybeUninit::new(()), PhantomData)
+    }
+}
+
+impl<T, I: Delta> RelPtr<T, I> {
+    /**
+     * Unsize the relative pointer, filling in the metadata of `U`,
+     * like an unsizing coercion of a normal pointer (e.g. `&[T; N]` to `&[T]`)
+     *
+     * This keeps the offset, so it points to the same value if the
+     * new relative pointer is in the same place as `self`, like `RelPtr::cast_delta`
+     *
+     * ```rust
+     * # fn main() {
+     * use rel_ptr::RelPtr;
+     *
+     * let mut data = ([1_u16, 2, 3], RelPtr::<[u16; 3], i8>::null());
+     * data.1.set(&mut data.0).unwrap();
+     *
+     * let slice = data.1.unsize::<[u16]>();
+     *
+     * assert_eq!(slice.offset(), data.1.offset());
+     * assert_eq!(unsafe { slice.metadata() }, 3);
+     * assert_eq!(unsafe { data.1.unsize_slice().metadata() }, 3);
+     * # }
+     * ```
+     */
+    #[inline]
+    pub fn unsize<U: ?Sized + MetaData>(self) -> RelPtr<U, I>
+    where
+        T: Unsize<U>,
+    {
+        RelPtr(self.0, MaybeUninit::new(T::unsize_data()), PhantomData)
+    }
+}
+
+impl<T, I: Delta, const N: usize> RelPtr<[T; N], I> {
+    /// Unsize the relative pointer to an array into a relative pointer to a slice, see `RelPtr::unsize`
+    #[inline]
+    pub fn unsize_slice(self) -> RelPtr<[T], I> {
+        self.unsize()
+    }
+}
+
+macro_rules! impl_from {
+    ($($from:ty => $($to:ty),*;)*) => {$($(
+        /// Widening the offset is lossless, see `RelPtr::cast_delta`
+        impl<T: ?Sized + MetaData> From<RelPtr<T, $from>> for RelPtr<T, $to> {
+            #[inline]
+            fn from(ptr: RelPtr<T, $from>) -> Self {
+                match ptr.cast_delta() {
+                    Ok(ptr) => ptr,
+                    Err(_) => unreachable!("widening an offset is lossless"),
+                }
+            }
+        }
+    )*)*};
+}
+
+macro_rules! impl_try_from {
+    ($($from:ty => $($to:ty),*;)*) => {$($(
+        /// Narrowing the offset may fail, see `RelPtr::cast_delta`
+        impl<T: ?Sized + MetaData> TryFrom<RelPtr<T, $from>> for RelPtr<T, $to> {
+            type Error = IntegerDeltaError;
+
+            #[inline]
+            fn try_from(ptr: RelPtr<T, $from>) -> Result<Self, Self::Error> {
+                ptr.cast_delta()
+            }
+        }
+    )*)*};
+}
+
+// these follow the lossless `From` conversions between the integers in `std`
+impl_from! {
+    i8 => i16, i32, i64, i128, isize, Auto;
+    i16 => i32, i64, i128, isize, Auto;
+    i32 => i64, i128, Auto;
+    i64 => i128, Auto;
+
+    NonZeroI8 => NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize, i8, i16, i32, i64, i128, isize, Auto;
+    NonZeroI16 => NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize, i16, i32, i64, i128, isize, Auto;
+    NonZeroI32 => NonZeroI64, NonZeroI128, i32, i64, i128, Auto;
+    NonZeroI64 => NonZeroI128, i64, i128, Auto;
+    NonZeroIsize => isize;
+
+    Auto => i64, i128;
+}
+
+impl_try_from! {
+    i8 => NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize;
+    i16 => i8, NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize;
+    i32 => i8, i16, isize, NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize;
+    i64 => i8, i16, i32, isize, NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize;
+    i128 => i8, i16, i32, i64, isize, NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroIsize, Auto;
+    isize => i8, i16, i32, i64, i128, NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize, Auto;
+
+    NonZeroI16 => i8, NonZeroI8;
+    NonZeroI32 => i8, i16, isize, NonZeroI8, NonZeroI16, NonZeroIsize;
+    NonZeroI64 => i8, i16, i32, isize, NonZeroI8, NonZeroI16, NonZeroI32, NonZeroIsize;
+    NonZeroI128 => i8, i16, i32, i64, isize, NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroIsize, Auto;
+    NonZeroIsize => i8, i16, i32, i64, i128, NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, Auto;
+
+    Auto => i8, i16, i32, isize, NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize;
+}
+
+// 128-bit offsets may not fit in an `isize`, so these convert directly instead of with `cast_delta`
+
+/// Widening the offset is lossless
+impl<T: ?Sized + MetaData> From<RelPtr<T, NonZeroI128>> for RelPtr<T, i128> {
+    #[inline]
+    fn from(ptr: RelPtr<T, NonZeroI128>) -> Self {
+        RelPtr(ptr.0.get(), ptr.1, PhantomData)
+    }
+}
+
+/// This only fails if the offset is 0
+impl<T: ?Sized + MetaData> TryFrom<RelPtr<T, i128>> for RelPtr<T, NonZeroI128> {
+    type Error = IntegerDeltaError;
+
+    #[inline]
+    fn try_from(ptr: RelPtr<T, i128>) -> Result<Self, Self::Error> {
+        match NonZeroI128::new(ptr.0) {
+            Some(del) => Ok(RelPtr(del, ptr.1, PhantomData)),
+            None => Err(IntegerDeltaError(IntegerDeltaErrorImpl::InvalidNonZero)),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use super::{RelPtr, MetaData, Delta, IntegerDelta, Error};
+
+impl<T: ?Sized + MetaData, I: IntegerDelta> RelPtr<T, I> {
+    /**
+     * Set `dest` to point to the same target as `self`, with the same metadata
+     *
+     * A relative pointer only points to its target while it stays in place relative
+     * to it, so a copy made by moving or returning it by value points somewhere else.
+     * `copy_to` computes the offset from `dest` instead, so it works across different
+     * `Delta` types. A null relative pointer (with an offset of 0) is copied as a null
+     * relative pointer, which fails if `J` is not `Nullable`.
+     *
+     * If the offset can't be stored in `J`, then `Err` is returned and `dest` is not changed
+     *
+     * ```rust
+     * # fn main() {
+     * use rel_ptr::RelPtr;
+     *
+     * let mut value = (10_u32, RelPtr::<u32, i8>::null());
+     * let mut far = Box::new(RelPtr::<u32, i64>::null());
+     *
+     * value.1.set(&mut value.0).unwrap();
+     * value.1.copy_to(&mut far).unwrap();
+     *
+     * assert_eq!(unsafe { *far.as_ref_unchecked() }, 10);
+     * # }
+     * ```
+     */
+    pub fn copy_to<J: Delta>(&self, dest: &mut RelPtr<T, J>) -> Result<(), Error>
+    where
+        J::Error: Into<Error>,
+    {
+        let dest_addr = dest as *mut RelPtr<T, J> as *mut u8;
+        let target = if self.0.to_isize() == 0 {
+            dest_addr
+        } else {
+            self.target_addr() as *mut u8
+        };
+
+        dest.0 = J::sub(target, dest_addr).map_err(Into::into)?;
+        dest.1 = self.1;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+/*!
+ * Formatting helpers for inspecting relative pointers
+ *
+ * `RelPtr` implements `Debug` without resolving the pointer or reading its metadata,
+ * use `WithMetadata` to show the metadata, and `DisplayTarget` to format the pointee as well
+ */
+
+use super::{RelPtr, MetaData, Delta, Nullable, IntegerDelta};
+
+use std::fmt;
+
+/**
+ * Formats the pointee of a relative pointer, or `null` if it is null
+ *
+ * The pointee is formatted with `Debug`, or with `Display` if it implements it.
+ * A relative pointer with an offset of 0 is null.
+ *
+ * ```rust
+ * # fn main() {
+ * use rel_ptr::{RelPtr, debug::DisplayTarget};
+ *
+ * let mut value = ("hello", RelPtr::<&str, i8>::null());
+ *
+ * assert_eq!(format!("{:?}", unsafe { DisplayTarget::new(&value.1) }), "null");
+ *
+ * value.1.set(&mut value.0).unwrap();
+ *
+ * assert_eq!(format!("{:?}", unsafe { DisplayTarget::new(&value.1) }), "\"hello\"");
+ * assert_eq!(format!("{}", unsafe { DisplayTarget::new(&value.1) }), "hello");
+ * # }
+ * ```
+ */
+pub struct DisplayTarget<'a, T: ?Sized + MetaData, I: IntegerDelta>(&'a RelPtr<T, I>);
+
+impl<'a, T: ?Sized + MetaData, I: IntegerDelta> DisplayTarget<'a, T, I> {
+    /**
+     * Create a new `DisplayTarget`
+     *
+     * # Safety
+     *
+     * The relative pointer must be null, or it must be safe to call `RelPtr::as_ref_unchecked`
+     * on it for as long as the `DisplayTarget` is used
+     */
+    #[inline]
+    pub unsafe fn new(ptr: &'a RelPtr<T, I>) -> Self {
+        Self(ptr)
+    }
+
+    /// The pointee, or `None` if the relative pointer is null
+    #[inline]
+    fn get(&self) -> Option<&'a T> {
+        if self.0.offset().to_isize() == 0 {
+            None
+        } else {
+            unsafe { Some(&*self.0.as_raw_unchecked_impl()) }
+        }
+    }
+}
+
+impl<T: ?Sized + MetaData + fmt::Debug, I: IntegerDelta> fmt::Debug for DisplayTarget<'_, T, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get() {
+            Some(value) => value.fmt(f),
+            None => f.write_str("null"),
+        }
+    }
+}
+
+impl<T: ?Sized + MetaData + fmt::Display, I: IntegerDelta> fmt::Display for DisplayTarget<'_, T, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get() {
+            Some(value) => value.fmt(f),
+            None => f.write_str("null"),
+        }
+    }
+}
+
+/**
+ * Formats a relative pointer like `{:#?}` does, and also shows its metadata,
+ * for example the length of a slice. The metadata of a null relative pointer is not shown.
+ *
+ * ```rust
+ * # fn main() {
+ * use rel_ptr::{RelPtr, debug::WithMetadata};
+ *
+ * let mut value = ([1_u8, 2, 3], RelPtr::<[u8], i8>::null());
+ *
+ * assert!(!format!("{:?}", unsafe { WithMetadata::new(&value.1) }).contains("metadata"));
+ *
+ * value.1.set(&mut value.0[1..]).unwrap();
+ *
+ * assert!(format!("{:?}", unsafe { WithMetadata::new(&value.1) }).contains("metadata: 2"));
+ * # }
+ * ```
+ */
+pub struct WithMetadata<'a, T: ?Sized + MetaData, I: Delta>(&'a RelPtr<T, I>);
+
+impl<'a, T: ?Sized + MetaData, I: Delta> WithMetadata<'a, T, I> {
+    /**
+     * Create a new `WithMetadata`
+     *
+     * # Safety
+     *
+     * The relative pointer must be null, or it must have been successfully set,
+     * so that its metadata is initialized
+     */
+    #[inline]
+    pub unsafe fn new(ptr: &'a RelPtr<T, I>) -> Self {
+        Self(ptr)
+    }
+}
+
+impl<T: ?Sized + MetaData, I: fmt::Debug + Nullable> fmt::Debug for WithMetadata<'_, T, I>
+where
+    T::Data: fmt::Debug
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut f = f.debug_struct("RelPtr");
+
+        f.field("ptr", &(self.0 as *const RelPtr<T, I>))
+            .field("offset", &self.0.offset());
+
+        if let Some(del) = self.0.offset().byte_offset() {
+            f.field("target", &(self.0 as *const RelPtr<T, I> as *const u8).wrapping_offset(del));
+        }
+
+        f.field("null", &self.0.is_null());
+
+        if !self.0.is_null() {
+            f.field("metadata", unsafe { &self.0.metadata() });
+        }
+
+        f.finish()
+    }
+}
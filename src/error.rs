@@ -1,14 +1,37 @@
+use crate::varint::VarintError;
 
 /**
  * If an integer's range is too small to store an offset, then
  * this error is generated
+ *
+ * Use `IntegerDeltaError::kind` to find out what went wrong, and
+ * `IntegerDeltaError::required_bits` to find out how large the offset
+ * type would need to be
  */
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct IntegerDeltaError(pub(crate) IntegerDeltaErrorImpl);
 
+/// The kinds of `IntegerDeltaError`, see `IntegerDeltaError::kind`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum IntegerDeltaErrorKind {
+    /// The offset is too large to be stored in the given integer type
+    Conversion,
+
+    /// The difference between the two pointers overflowed `isize`
+    Overflow,
+
+    /// The offset was zero (the relative pointer points to itself)
+    /// when a `NonZero*` type was used
+    InvalidNonZero,
+
+    /// The offset does not leave enough low bits free to store a tag
+    Misaligned,
+}
+
 /// All types of errors, this is internal and so protected
 /// behind a wrapper struct
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum IntegerDeltaErrorImpl {
     /// Failed to convert isize to given integer type
     Conversion(isize),
@@ -17,12 +40,125 @@ pub(crate) enum IntegerDeltaErrorImpl {
     Sub(usize, usize),
 
     /// Got a zero when a non-zero value was expected (for `NonZero*`)
-    InvalidNonZero
+    InvalidNonZero,
+
+    /// The offset does not leave enough low bits free to store a tag
+    Misaligned(isize),
+
+    /// Calculating the new offset overflowed isize
+    Overflow,
+
+    /// The stored offset doesn't fit in an isize (for 128-bit offsets)
+    Truncated(i128),
+}
+
+/// The number of bits needed to store `del` in a signed integer
+fn signed_bits(del: i128) -> u32 {
+    let bits = (std::mem::size_of::<i128>() * 8) as u32;
+
+    bits - (del ^ (del >> (bits - 1))).leading_zeros() + 1
+}
+
+impl IntegerDeltaError {
+    /// What went wrong
+    pub fn kind(&self) -> IntegerDeltaErrorKind {
+        match self.0 {
+            IntegerDeltaErrorImpl::Conversion(_) |
+            IntegerDeltaErrorImpl::Truncated(_) => IntegerDeltaErrorKind::Conversion,
+            IntegerDeltaErrorImpl::Sub(..) |
+            IntegerDeltaErrorImpl::Overflow => IntegerDeltaErrorKind::Overflow,
+            IntegerDeltaErrorImpl::InvalidNonZero => IntegerDeltaErrorKind::InvalidNonZero,
+            IntegerDeltaErrorImpl::Misaligned(_) => IntegerDeltaErrorKind::Misaligned,
+        }
+    }
+
+    /// The offset that could not be stored,
+    /// if it could be represented as an `isize`
+    pub fn attempted_offset(&self) -> Option<isize> {
+        match self.0 {
+            IntegerDeltaErrorImpl::Conversion(del) |
+            IntegerDeltaErrorImpl::Misaligned(del) => Some(del),
+            IntegerDeltaErrorImpl::InvalidNonZero => Some(0),
+            IntegerDeltaErrorImpl::Sub(..) |
+            IntegerDeltaErrorImpl::Truncated(_) |
+            IntegerDeltaErrorImpl::Overflow => None,
+        }
+    }
+
+    /**
+     * The number of bits a signed integer offset would need to store
+     * the attempted offset
+     *
+     * This is `None` if using a larger integer would not fix the error,
+     * i.e. for `InvalidNonZero`, `Misaligned`, and offsets that don't fit in `isize`
+     */
+    pub fn required_bits(&self) -> Option<u32> {
+        match self.0 {
+            IntegerDeltaErrorImpl::Conversion(del) => Some(signed_bits(del as i128)),
+            IntegerDeltaErrorImpl::InvalidNonZero |
+            IntegerDeltaErrorImpl::Truncated(_) |
+            IntegerDeltaErrorImpl::Sub(..) |
+            IntegerDeltaErrorImpl::Misaligned(_) |
+            IntegerDeltaErrorImpl::Overflow => None,
+        }
+    }
 }
 
 #[cfg(not(feature = "no_std"))]
 impl std::error::Error for IntegerDeltaError {}
 
+/**
+ * A general error for relative pointers
+ *
+ * This can be used as the `Delta::Error` of custom `Delta` types,
+ * so that errors can be handled the same way for all `Delta`s
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// An error from one of the integer `Delta`s
+    Integer(IntegerDeltaError),
+
+    /// An error from reading or writing a varint offset
+    Varint(VarintError),
+
+    /// An error from a custom `Delta`
+    Custom(&'static str),
+}
+
+impl Error {
+    /// Get the `IntegerDeltaError`, if this error came from an integer `Delta`
+    pub fn as_integer(&self) -> Option<&IntegerDeltaError> {
+        match self {
+            Error::Integer(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<IntegerDeltaError> for Error {
+    fn from(err: IntegerDeltaError) -> Self {
+        Error::Integer(err)
+    }
+}
+
+impl From<VarintError> for Error {
+    fn from(err: VarintError) -> Self {
+        Error::Varint(err)
+    }
+}
+
+#[cfg(not(feature = "no_std"))]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Integer(err) => Some(err),
+            Error::Varint(err) => Some(err),
+            Error::Custom(_) => None,
+        }
+    }
+}
+
 mod fmt {
     use super::*;
     use std::fmt;
@@ -41,7 +177,33 @@ mod fmt {
                 
                 IntegerDeltaErrorImpl::InvalidNonZero => {
                     write!(f, "Difference was zero when a `NonZero*` type was specified")
-                }
+                },
+
+                IntegerDeltaErrorImpl::Misaligned(del) => write!(
+                    f,
+                    "Offset could not be tagged (offset of {} is not aligned enough)",
+                    del
+                ),
+
+                IntegerDeltaErrorImpl::Overflow => {
+                    write!(f, "Calculating the new offset overflows `isize`")
+                },
+
+                IntegerDeltaErrorImpl::Truncated(del) => write!(
+                    f,
+                    "Offset could not be used (offset of {} does not fit in `isize`)",
+                    del
+                ),
+            }
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Error::Integer(err) => fmt::Display::fmt(err, f),
+                Error::Varint(err) => fmt::Display::fmt(err, f),
+                Error::Custom(msg) => f.write_str(msg),
             }
         }
     }
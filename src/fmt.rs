@@ -8,11 +8,78 @@ impl<T: ?Sized + MetaData, I: Debug + Delta> Pointer for RelPtr<T, I> {
     }
 }
 
+/**
+ * `{:?}` shows the address of the relative pointer and its offset, `{:#?}` also
+ * shows the address of the target and whether it is null, if the offset is stored
+ * as a plain number of bytes (see `Delta::byte_offset`)
+ *
+ * This never reads the metadata, because it may not be initialized,
+ * use `debug::WithMetadata` to show it, and `debug::DisplayTarget` to format the pointee
+ */
 impl<T: ?Sized + MetaData, I: Debug + Delta> Debug for RelPtr<T, I> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        f.debug_struct("RelPtr")
+        let alternate = f.alternate();
+        let mut f = f.debug_struct("RelPtr");
+
+        f.field("ptr", &(self as *const Self))
+            .field("offset", &self.0);
+
+        if let (true, Some(del)) = (alternate, self.0.byte_offset()) {
+            f.field("target", &(self as *const Self as *const u8).wrapping_offset(del))
+                .field("null", &(del == 0));
+        }
+
+        f.finish()
+    }
+}
+
+/// Like `RelPtr`, `{:#?}` also shows the address of the target and whether it is null
+impl<T: ?Sized + MetaData, I: Debug + IntegerDelta, const BITS: u32> Debug for TaggedRelPtr<T, I, BITS> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let alternate = f.alternate();
+        let mut f = f.debug_struct("TaggedRelPtr");
+
+        f.field("ptr", &(self as *const Self))
+            .field("offset", &self.0 .0)
+            .field("tag", &self.tag());
+
+        if let (true, Ok(offset)) = (alternate, self.0 .0.try_to_isize()) {
+            let offset = offset & !(Self::TAG_MASK as isize);
+
+            f.field("target", &(self as *const Self as *const u8).wrapping_offset(offset))
+                .field("null", &(offset == 0));
+        }
+
+        f.finish()
+    }
+}
+
+/// Formats like the underlying `RelPtr`, which is at the same address
+impl<T: ?Sized + MetaData, I: Debug + Delta> Debug for RelConst<T, I> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_tuple("RelConst").field(&self.0).finish()
+    }
+}
+
+/// Formats like the underlying `RelPtr`, which is at the same address
+impl<T: ?Sized + MetaData, I: Debug + Delta> Debug for RelMut<T, I> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_tuple("RelMut").field(&self.0).finish()
+    }
+}
+
+/// Like `RelPtr`, `{:?}` shows the address of the relative function pointer and its offset
+impl<F: FnPtr, I: Debug + Delta> Debug for RelFn<F, I> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_struct("RelFn")
             .field("ptr", &(self as *const Self))
-            .field("offset", &self.0)
+            .field("offset", &self.offset())
             .finish()
     }
 }
+
+impl<F: FnPtr, I: Debug + Delta, const N: usize> Debug for RelVTable<F, I, N> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_tuple("RelVTable").field(self.offsets()).finish()
+    }
+}
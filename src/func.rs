@@ -0,0 +1,332 @@
+use super::{Delta, Nullable, IntegerDelta, Error};
+
+use core::mem::MaybeUninit;
+use std::marker::PhantomData;
+
+/**
+ * A function pointer type, like `fn(u32) -> u32`, see `RelFn`
+ *
+ * This is implemented for safe and `unsafe` function pointers, with the Rust and
+ * `"C"` ABIs, with up to 8 arguments. Function pointers with higher-ranked lifetimes,
+ * like `for<'a> fn(&'a u8)`, are not covered.
+ *
+ * # Safety
+ *
+ * `Self` must be a function pointer, and `from_addr(f.addr())` must be `f`
+ */
+pub unsafe trait FnPtr: Copy {
+    /// The address of the function
+    fn addr(self) -> *const u8;
+
+    /**
+     * The function at `addr`
+     *
+     * # Safety
+     *
+     * `addr` must be the address of a function with the signature of `Self`
+     */
+    unsafe fn from_addr(addr: *const u8) -> Self;
+}
+
+macro_rules! impl_fn_ptr {
+    ($($arg:ident)*) => {
+        impl_fn_ptr! { @impl [$($arg)*] fn($($arg),*) -> R }
+        impl_fn_ptr! { @impl [$($arg)*] unsafe fn($($arg),*) -> R }
+        impl_fn_ptr! { @impl [$($arg)*] extern "C" fn($($arg),*) -> R }
+        impl_fn_ptr! { @impl [$($arg)*] unsafe extern "C" fn($($arg),*) -> R }
+    };
+    (@impl [$($arg:ident)*] $type:ty) => {
+        unsafe impl<R, $($arg),*> FnPtr for $type {
+            #[inline(always)]
+            fn addr(self) -> *const u8 {
+                self as *const u8
+            }
+
+            #[inline(always)]
+            unsafe fn from_addr(addr: *const u8) -> Self {
+                std::mem::transmute::<*const u8, Self>(addr)
+            }
+        }
+    };
+}
+
+impl_fn_ptr! {}
+impl_fn_ptr! { A }
+impl_fn_ptr! { A B }
+impl_fn_ptr! { A B C }
+impl_fn_ptr! { A B C D }
+impl_fn_ptr! { A B C D E }
+impl_fn_ptr! { A B C D E F }
+impl_fn_ptr! { A B C D E F G }
+impl_fn_ptr! { A B C D E F G H }
+
+/**
+ * A relative function pointer, which stores the offset from itself to a function
+ *
+ * Like `RelPtr`, it only points to the function while the distance between them stays the
+ * same. Code doesn't move, so this is when the `RelFn` is in a `static` or in the code of the
+ * same module, which the loader moves as a whole, or when it is not moved at all. A `RelFn`
+ * that is moved on its own doesn't point to the function anymore, use `RelFn::copy_to` to
+ * copy it somewhere else. For tables of functions that can be moved, copied or written into
+ * relocatable images, use `RelVTable`.
+ *
+ * A null relative function pointer has an offset of 0, like `RelPtr`.
+ *
+ * ```rust
+ * # fn main() {
+ * use rel_ptr::RelFn;
+ *
+ * fn double(x: u32) -> u32 {
+ *     x * 2
+ * }
+ *
+ * let mut ptr = RelFn::<fn(u32) -> u32>::null();
+ * assert!(unsafe { ptr.get() }.is_none());
+ *
+ * ptr.set(double).unwrap();
+ *
+ * assert_eq!(unsafe { ptr.get_unchecked() }(21), 42);
+ * # }
+ * ```
+ *
+ * # Safety
+ *
+ * The function can only be resolved if the `RelFn` was set, and it hasn't moved since,
+ * relative to the function
+ */
+#[repr(transparent)]
+pub struct RelFn<F: FnPtr, I: Delta = isize>(I, PhantomData<F>);
+
+impl<F: FnPtr, I: Delta> Copy for RelFn<F, I> {}
+impl<F: FnPtr, I: Delta> Clone for RelFn<F, I> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F: FnPtr, I: Nullable> RelFn<F, I> {
+    /// A null relative function pointer has an offset of 0, (points to itself)
+    #[inline(always)]
+    pub fn null() -> Self {
+        Self(I::NULL, PhantomData)
+    }
+
+    /// Check if the relative function pointer is null
+    #[inline(always)]
+    pub fn is_null(&self) -> bool {
+        self.0 == I::NULL
+    }
+
+    /**
+     * Gets the function from the relative function pointer,
+     * if it is null, then `None` is returned
+     *
+     * # Safety
+     *
+     * Same as `RelFn::get_unchecked`, if it is not null
+     */
+    #[inline]
+    pub unsafe fn get(&self) -> Option<F> {
+        if self.is_null() {
+            None
+        } else {
+            Some(self.get_unchecked())
+        }
+    }
+}
+
+impl<F: FnPtr, I: Delta> From<I> for RelFn<F, I> {
+    /// A relative function pointer with the given offset, which must be set before use
+    #[inline(always)]
+    fn from(i: I) -> Self {
+        Self(i, PhantomData)
+    }
+}
+
+impl<F: FnPtr, I: Delta> RelFn<F, I> {
+    /// The offset stored in the relative function pointer
+    #[inline(always)]
+    pub fn offset(&self) -> I {
+        self.0
+    }
+
+    /**
+     * Set the relative function pointer to point to `f`,
+     * if the offset cannot be calculated using the given
+     * `Delta`, then `Err` will be returned, and there will be
+     * **no** change to the offset
+     */
+    #[inline]
+    pub fn set(&mut self, f: F) -> Result<(), I::Error> {
+        self.0 = I::sub(f.addr() as *mut u8, self as *mut Self as *mut u8)?;
+
+        Ok(())
+    }
+
+    /**
+     * Gets the function from the relative function pointer
+     *
+     * # Safety
+     *
+     * The relative function pointer must have been successfully set, and it must not
+     * have moved since, relative to the function
+     */
+    #[inline]
+    pub unsafe fn get_unchecked(&self) -> F {
+        F::from_addr(self.0.add(self as *const Self as *const u8))
+    }
+}
+
+impl<F: FnPtr, I: IntegerDelta> RelFn<F, I> {
+    /**
+     * Set `dest` to point to the same function as `self`, see `RelPtr::copy_to`
+     *
+     * A null relative function pointer is copied as a null relative function pointer.
+     * If the offset can't be stored in `J`, then `Err` is returned and `dest` is not changed
+     */
+    pub fn copy_to<J: Delta>(&self, dest: &mut RelFn<F, J>) -> Result<(), Error>
+    where
+        J::Error: Into<Error>,
+    {
+        let dest_addr = dest as *mut RelFn<F, J> as *mut u8;
+        let target = if self.0.to_isize() == 0 {
+            dest_addr
+        } else {
+            (self as *const Self as *mut u8).wrapping_offset(self.0.to_isize())
+        };
+
+        dest.0 = J::sub(target, dest_addr).map_err(Into::into)?;
+
+        Ok(())
+    }
+}
+
+/// The fixed point in the code that `RelVTable` entries are relative to
+#[inline(never)]
+fn anchor() {}
+
+/**
+ * A table of functions that can be moved, copied and written into relocatable images,
+ * like a vtable for plugins
+ *
+ * The entries are stored as offsets from a fixed function in this crate, instead of
+ * from the table like `RelFn`. So the table is plain data, which stays valid wherever it is
+ * moved or copied to, as long as it is used by the same build of the program that set it,
+ * because the loader moves all of the code of a program as a whole. A table can't be
+ * used by a different program, or across dynamically loaded libraries, because their code
+ * is loaded at unrelated addresses.
+ *
+ * ```rust
+ * # fn main() {
+ * use rel_ptr::RelVTable;
+ *
+ * fn add(a: u32, b: u32) -> u32 { a + b }
+ * fn mul(a: u32, b: u32) -> u32 { a * b }
+ *
+ * let table = RelVTable::<fn(u32, u32) -> u32, i64, 2>::new([add, mul]).unwrap();
+ *
+ * // the table stays valid after it is moved
+ * let table = Box::new(table);
+ *
+ * unsafe {
+ *     assert_eq!(table.get(0).unwrap()(3, 4), 7);
+ *     assert_eq!(table.get(1).unwrap()(3, 4), 12);
+ *     assert!(table.get(2).is_none());
+ * }
+ * # }
+ * ```
+ *
+ * # Safety
+ *
+ * The entries can only be resolved by the same build of the program that set them
+ */
+#[repr(transparent)]
+pub struct RelVTable<F: FnPtr, I: Delta, const N: usize>([I; N], PhantomData<F>);
+
+impl<F: FnPtr, I: Delta, const N: usize> Copy for RelVTable<F, I, N> {}
+impl<F: FnPtr, I: Delta, const N: usize> Clone for RelVTable<F, I, N> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F: FnPtr, I: Delta, const N: usize> RelVTable<F, I, N> {
+    #[inline(always)]
+    fn anchor() -> *mut u8 {
+        (anchor as fn()).addr() as *mut u8
+    }
+
+    /**
+     * Create a table of the given functions,
+     * if the offset of any function cannot be calculated using the given
+     * `Delta`, then `Err` will be returned
+     */
+    pub fn new(fns: [F; N]) -> Result<Self, I::Error> {
+        let mut offsets: [MaybeUninit<I>; N] = unsafe { MaybeUninit::uninit().assume_init() };
+
+        for (offset, f) in offsets.iter_mut().zip(fns.iter()) {
+            *offset = MaybeUninit::new(I::sub(f.addr() as *mut u8, Self::anchor())?);
+        }
+
+        // every offset was initialized, and `I: Copy`, so there is nothing to drop
+        Ok(Self(unsafe { std::mem::transmute_copy(&offsets) }, PhantomData))
+    }
+
+    /**
+     * Create a table from offsets that were read from somewhere else,
+     * like `RelVTable::offsets` in a relocatable image
+     */
+    #[inline(always)]
+    pub fn from_offsets(offsets: [I; N]) -> Self {
+        Self(offsets, PhantomData)
+    }
+
+    /// The offsets of the entries in the table
+    #[inline(always)]
+    pub fn offsets(&self) -> &[I; N] {
+        &self.0
+    }
+
+    /// The number of entries in the table
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        N
+    }
+
+    /// Check if the table has no entries
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /**
+     * Set the entry at `index` to `f`,
+     * if the offset cannot be calculated using the given
+     * `Delta`, then `Err` will be returned, and there will be
+     * **no** change to the table
+     *
+     * # Panics
+     *
+     * If `index >= N`
+     */
+    #[inline]
+    pub fn set(&mut self, index: usize, f: F) -> Result<(), I::Error> {
+        self.0[index] = I::sub(f.addr() as *mut u8, Self::anchor())?;
+
+        Ok(())
+    }
+
+    /**
+     * Gets the function at `index`, or `None` if `index >= N`
+     *
+     * # Safety
+     *
+     * The entry must have been set by the same build of the program, see `RelVTable`
+     */
+    #[inline]
+    pub unsafe fn get(&self, index: usize) -> Option<F> {
+        let offset = *self.0.get(index)?;
+
+        Some(F::from_addr(offset.add(Self::anchor())))
+    }
+}
@@ -0,0 +1,345 @@
+use super::{RelPtr, RelMut, Nullable, IntegerDelta, IntegerDeltaError};
+
+use std::fmt;
+use std::mem;
+use std::ops::{Index, IndexMut};
+
+/// A value and its link, the link is first so that it can never point to itself
+#[repr(C)]
+struct Node<T, I: IntegerDelta + Nullable> {
+    link: RelMut<T, I>,
+    value: T,
+}
+
+/**
+ * A growable array of values that link to each other with relative pointers
+ *
+ * Every value has one link, which is a relative pointer to another value in the
+ * same `RelGraphVec` (or to itself), or null. All of the values are stored contiguously,
+ * so when the buffer is reallocated they move together, and the links stay valid without
+ * any fix ups. Operations that move values relative to each other, like `swap_remove`,
+ * fix up the links, and the ones that can't be fixed up cheaply, like `insert` or sorting,
+ * are not provided.
+ *
+ * The offset type `I` limits how far apart linked values can be, so `link` and
+ * `swap_remove` return `Err` if an offset doesn't fit. The values can be mutated
+ * in place, but not the links, except through `link` and `unlink`.
+ *
+ * `T` can't be zero sized, because then the link to the previous value would have an
+ * offset of 0, which is null
+ *
+ * ```compile_fail
+ * let graph = rel_ptr::RelGraphVec::<(), isize>::new();
+ * ```
+ *
+ * ```rust
+ * # #[cfg(feature = "no_std")] fn main() {}
+ * # #[cfg(not(feature = "no_std"))] fn main() {
+ * use rel_ptr::RelGraphVec;
+ *
+ * let mut graph = RelGraphVec::<&str, i32>::new();
+ *
+ * let a = graph.push("a");
+ * let b = graph.push("b");
+ * graph.link(a, b).unwrap();
+ *
+ * // reallocating moves all of the values together
+ * for _ in 0..100 {
+ *     graph.push("filler");
+ * }
+ *
+ * assert_eq!(graph.follow(a), Some(b));
+ * assert_eq!(graph.linked(a), Some(&"b"));
+ *
+ * // `a` is moved into the place of the removed value, and its link is fixed up
+ * graph.swap_remove(0).unwrap();
+ * graph.swap_remove(0).unwrap();
+ *
+ * assert_eq!(graph.linked(0), None);
+ * # }
+ * ```
+ */
+pub struct RelGraphVec<T, I: IntegerDelta + Nullable = isize> {
+    nodes: Vec<Node<T, I>>,
+}
+
+impl<T, I: IntegerDelta + Nullable> Default for RelGraphVec<T, I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, I: IntegerDelta + Nullable> RelGraphVec<T, I> {
+    /// The offset of the value in a node
+    const VALUE_OFFSET: usize = {
+        let align = mem::align_of::<T>();
+
+        (mem::size_of::<RelMut<T, I>>() + align - 1) / align * align
+    };
+
+    /// Fails to compile if `T` is zero sized, see `RelGraphVec`
+    const NOT_ZERO_SIZED: () = [()][(mem::size_of::<T>() == 0) as usize];
+
+    /// Create an empty `RelGraphVec`
+    #[inline]
+    pub fn new() -> Self {
+        let () = Self::NOT_ZERO_SIZED;
+
+        Self { nodes: Vec::new() }
+    }
+
+    /// Create an empty `RelGraphVec` with space for at least `capacity` values
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        let () = Self::NOT_ZERO_SIZED;
+
+        Self { nodes: Vec::with_capacity(capacity) }
+    }
+
+    /// The number of values
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Check if there are no values
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The number of values that can be stored without reallocating
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.nodes.capacity()
+    }
+
+    /// Reserve space for at least `additional` more values, the links stay valid
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.nodes.reserve(additional)
+    }
+
+    /// Shrink the capacity as much as possible, the links stay valid
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.nodes.shrink_to_fit()
+    }
+
+    /// Add a value with a null link to the end, and return its index
+    pub fn push(&mut self, value: T) -> usize {
+        self.nodes.push(Node { link: RelMut::null(), value });
+        self.nodes.len() - 1
+    }
+
+    /// Get the value at `index`, or `None` if it is out of bounds
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.nodes.get(index).map(|node| &node.value)
+    }
+
+    /// Get the value at `index` mutably, or `None` if it is out of bounds
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.nodes.get_mut(index).map(|node| &mut node.value)
+    }
+
+    /// Iterate over the values, in order
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.nodes.iter().map(|node| &node.value)
+    }
+
+    /// Iterate over the values mutably, in order
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.nodes.iter_mut().map(|node| &mut node.value)
+    }
+
+    /// The offset from the link of the node at `from` to the value of the node at `to`
+    fn offset(from: usize, to: usize) -> Result<I, IntegerDeltaError> {
+        let size = mem::size_of::<Node<T, I>>() as isize;
+
+        I::from_isize((to as isize - from as isize) * size + Self::VALUE_OFFSET as isize)
+    }
+
+    /**
+     * Link the value at `from` to the value at `to`, replacing its old link
+     *
+     * If the offset between them cannot be stored in `I`, then `Err` is returned,
+     * and there is **no** change to the link
+     *
+     * # Panics
+     *
+     * If `from` or `to` is out of bounds
+     */
+    pub fn link(&mut self, from: usize, to: usize) -> Result<(), IntegerDeltaError> {
+        let len = self.nodes.len();
+
+        assert!(from < len && to < len, "tried to link {} to {}, but the length is {}", from, to, len);
+
+        self.nodes[from].link = RelMut::from(RelPtr::from(Self::offset(from, to)?));
+
+        Ok(())
+    }
+
+    /**
+     * Remove the link from the value at `index`
+     *
+     * # Panics
+     *
+     * If `index` is out of bounds
+     */
+    pub fn unlink(&mut self, index: usize) {
+        self.nodes[index].link = RelMut::null();
+    }
+
+    /**
+     * The index of the value that the value at `index` links to,
+     * or `None` if its link is null
+     *
+     * # Panics
+     *
+     * If `index` is out of bounds
+     */
+    pub fn follow(&self, index: usize) -> Option<usize> {
+        let link = &self.nodes[index].link;
+
+        if link.is_null() {
+            return None;
+        }
+
+        let size = mem::size_of::<Node<T, I>>() as isize;
+        let del = (link.as_rel_ptr().offset().to_isize() - Self::VALUE_OFFSET as isize) / size;
+
+        Some((index as isize + del) as usize)
+    }
+
+    /**
+     * The value that the value at `index` links to, or `None` if its link is null
+     *
+     * # Panics
+     *
+     * If `index` is out of bounds
+     */
+    #[inline]
+    pub fn linked(&self, index: usize) -> Option<&T> {
+        self.follow(index).map(|to| &self.nodes[to].value)
+    }
+
+    /**
+     * The value that the value at `index` links to mutably, or `None` if its link is null
+     *
+     * # Panics
+     *
+     * If `index` is out of bounds
+     */
+    #[inline]
+    pub fn linked_mut(&mut self, index: usize) -> Option<&mut T> {
+        let to = self.follow(index)?;
+
+        Some(&mut self.nodes[to].value)
+    }
+
+    /**
+     * Remove the last value and return it, links to it become null
+     *
+     * Unlike `Vec::pop`, this takes linear time, because every link is checked
+     */
+    pub fn pop(&mut self) -> Option<T> {
+        let last = self.nodes.len().checked_sub(1)?;
+
+        for index in 0..last {
+            if self.follow(index) == Some(last) {
+                self.unlink(index);
+            }
+        }
+
+        self.nodes.pop().map(|node| node.value)
+    }
+
+    /// Remove all of the values
+    #[inline]
+    pub fn clear(&mut self) {
+        self.nodes.clear()
+    }
+
+    /**
+     * Remove the value at `index` and return it, replacing it with the last value
+     *
+     * Links to the removed value become null, and the links to and from the last value
+     * are fixed up to its new place. This takes linear time, because every link is
+     * checked. If any of the new offsets can't be stored in `I`, then `Err` is returned,
+     * and there is **no** change to the `RelGraphVec`
+     *
+     * # Panics
+     *
+     * If `index` is out of bounds
+     */
+    pub fn swap_remove(&mut self, index: usize) -> Result<T, IntegerDeltaError> {
+        let len = self.nodes.len();
+
+        assert!(index < len, "tried to remove {}, but the length is {}", index, len);
+
+        let last = len - 1;
+        let new_place = |i: usize| if i == last { index } else { i };
+
+        // calculate all of the new offsets first, so that nothing changes on error
+        let mut links = Vec::with_capacity(len);
+
+        for from in 0..len {
+            let link = match self.follow(from) {
+                Some(to) if to != index && from != index => Some(Self::offset(new_place(from), new_place(to))?),
+                _ => None,
+            };
+
+            links.push(link);
+        }
+
+        let node = self.nodes.swap_remove(index);
+
+        for (from, link) in links.into_iter().enumerate().filter(|&(from, _)| from != index) {
+            self.nodes[new_place(from)].link = match link {
+                Some(offset) => RelMut::from(RelPtr::from(offset)),
+                None => RelMut::null(),
+            };
+        }
+
+        Ok(node.value)
+    }
+}
+
+impl<T, I: IntegerDelta + Nullable> Index<usize> for RelGraphVec<T, I> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        &self.nodes[index].value
+    }
+}
+
+impl<T, I: IntegerDelta + Nullable> IndexMut<usize> for RelGraphVec<T, I> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.nodes[index].value
+    }
+}
+
+/// The clone has the same links, because the values stay in the same places relative to each other
+impl<T: Clone, I: IntegerDelta + Nullable> Clone for RelGraphVec<T, I> {
+    fn clone(&self) -> Self {
+        let nodes = self.nodes.iter().map(|node| Node {
+            link: RelMut::from(RelPtr::from(node.link.as_rel_ptr().offset())),
+            value: node.value.clone(),
+        });
+
+        Self { nodes: nodes.collect() }
+    }
+}
+
+/// Shows each value with the index that it links to
+impl<T: fmt::Debug, I: IntegerDelta + Nullable> fmt::Debug for RelGraphVec<T, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.iter().enumerate().map(|(index, value)| (value, self.follow(index))))
+            .finish()
+    }
+}
@@ -0,0 +1,933 @@
+/*!
+ * Inspecting relocatable images, enabled with the `inspect` feature
+ *
+ * This requires `std`, and is used by the `rel-ptr-inspect` binary
+ *
+ * The layout of an image is described by a `Schema`, starting from a root value,
+ * every relative pointer that can be reached is followed, and the values that they point
+ * to are recorded in a `Report`. Relative pointers that point out of the image, or to a
+ * misaligned value are flagged, as are values that partially overlap each other.
+ * The report can be written as an annotated hexdump, or as a Graphviz DOT graph.
+ *
+ * A schema is a list of `repr(C)` structs,
+ *
+ * ```text
+ * # a linked list of names
+ * struct Node {
+ *     value: u32,
+ *     next: ptr<i32, Node>,
+ *     name: str<i32>,
+ *     tags: slice<i32, [u8; 2]>,
+ * }
+ * ```
+ *
+ * Field types are primitives (`u8` to `u64`, `i8` to `i64`, `usize`, `isize`, `f32`, `f64`,
+ * `bool` and `char`), arrays `[T; N]`, other structs, and relative pointers: `ptr<I, T>`
+ * for `RelPtr<T, I>`, `slice<I, T>` for `RelPtr<[T], I>` and `str<I>` for `RelPtr<str, I>`.
+ * The offset `I` is one of `i8`, `i16`, `i32`, `i64` or `isize`.
+ * Comments start with `#` or `//`.
+ */
+
+use super::{RelPtr, IntegerDelta};
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+use std::mem::{self, MaybeUninit};
+use std::{fmt, ptr};
+
+/// An error in a schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError(String);
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid schema: {}", self.0)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+macro_rules! schema_error {
+    ($($args:tt)*) => {
+        SchemaError(format!($($args)*))
+    };
+}
+
+/// The offset type of a relative pointer in a schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeltaKind {
+    /// `i8`
+    I8,
+    /// `i16`
+    I16,
+    /// `i32`
+    I32,
+    /// `i64`
+    I64,
+    /// `isize`
+    Isize,
+}
+
+/// The type of a field in a schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    /// A primitive, with its name
+    Prim(&'static str),
+    /// `[T; N]`
+    Array(Box<Type>, usize),
+    /// A struct, with its index in the schema
+    Struct(usize),
+    /// `ptr<I, T>`, a `RelPtr<T, I>`
+    Ptr(DeltaKind, Box<Type>),
+    /// `slice<I, T>`, a `RelPtr<[T], I>`
+    Slice(DeltaKind, Box<Type>),
+    /// `str<I>`, a `RelPtr<str, I>`
+    Str(DeltaKind),
+}
+
+/// A field of a struct in a schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    /// The name of the field
+    pub name: String,
+    /// The type of the field
+    pub ty: Type,
+    /// The offset of the field from the start of the struct
+    pub offset: usize,
+}
+
+/// A `repr(C)` struct in a schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Struct {
+    /// The name of the struct
+    pub name: String,
+    /// The fields of the struct, in order
+    pub fields: Vec<Field>,
+    /// The size of the struct
+    pub size: usize,
+    /// The alignment of the struct
+    pub align: usize,
+}
+
+/// A description of the layout of an image, see the module docs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schema {
+    structs: Vec<Struct>,
+}
+
+/// The size and alignment of `T`
+const fn layout<T>() -> (usize, usize) {
+    (mem::size_of::<T>(), mem::align_of::<T>())
+}
+
+/// The primitives, with their size and alignment on this target
+const PRIMS: [(&str, (usize, usize)); 14] = [
+    ("u8", layout::<u8>()), ("u16", layout::<u16>()), ("u32", layout::<u32>()),
+    ("u64", layout::<u64>()), ("usize", layout::<usize>()),
+    ("i8", layout::<i8>()), ("i16", layout::<i16>()), ("i32", layout::<i32>()),
+    ("i64", layout::<i64>()), ("isize", layout::<isize>()),
+    ("f32", layout::<f32>()), ("f64", layout::<f64>()), ("bool", layout::<bool>()), ("char", layout::<char>()),
+];
+
+/// The size and alignment of the primitive `name`
+fn prim_layout(name: &str) -> (usize, usize) {
+    PRIMS.iter().find(|(prim, _)| *prim == name).unwrap().1
+}
+
+/// The size and alignment of `[T; len]`, or `None` if it is larger than `isize::MAX`
+fn array_layout((size, align): (usize, usize), len: usize) -> Option<(usize, usize)> {
+    size.checked_mul(len).filter(|&size| size <= isize::MAX as usize).map(|size| (size, align))
+}
+
+/// Round `size` up to a multiple of `align`, or `None` if that overflows
+fn align_up(size: usize, align: usize) -> Option<usize> {
+    size.checked_add(align - 1).map(|size| size / align * align)
+}
+
+/// The size and alignment of a relative pointer, read from the real `RelPtr`
+fn rel_ptr_layout(delta: DeltaKind, fat: bool) -> (usize, usize) {
+    macro_rules! by_delta {
+        ($($kind:ident $type:ty),*) => {
+            match delta {
+                $(DeltaKind::$kind => if fat {
+                    layout::<RelPtr<[u8], $type>>()
+                } else {
+                    layout::<RelPtr<u8, $type>>()
+                },)*
+            }
+        };
+    }
+
+    by_delta!(I8 i8, I16 i16, I32 i32, I64 i64, Isize isize)
+}
+
+/// Read the offset (and metadata if `fat`) of the relative pointer at `pos`, which must be in the image
+fn read_rel_ptr(image: &[u8], pos: usize, delta: DeltaKind, fat: bool) -> (isize, usize) {
+    fn read<T: ?Sized + crate::MetaData<Data = D>, D: Copy, I: IntegerDelta>(image: &[u8], pos: usize) -> (isize, D) {
+        let mut ptr = MaybeUninit::<RelPtr<T, I>>::uninit();
+        let bytes = &image[pos..pos + mem::size_of::<RelPtr<T, I>>()];
+
+        unsafe {
+            ptr::copy_nonoverlapping(bytes.as_ptr(), ptr.as_mut_ptr() as *mut u8, bytes.len());
+
+            let ptr = ptr.assume_init();
+            let offset = ptr.offset().to_isize();
+
+            (offset, ptr.metadata())
+        }
+    }
+
+    macro_rules! by_delta {
+        ($($kind:ident $type:ty),*) => {
+            match delta {
+                $(DeltaKind::$kind => if fat {
+                    read::<[u8], usize, $type>(image, pos)
+                } else {
+                    (read::<u8, (), $type>(image, pos).0, 1)
+                },)*
+            }
+        };
+    }
+
+    by_delta!(I8 i8, I16 i16, I32 i32, I64 i64, Isize isize)
+}
+
+impl Schema {
+    /// Parse a schema, see the module docs for the syntax
+    pub fn parse(text: &str) -> Result<Self, SchemaError> {
+        let tokens = tokenize(text)?;
+        let mut raw = Vec::new();
+        let mut rest = &tokens[..];
+
+        while !rest.is_empty() {
+            let (name, fields, next) = parse_struct(rest)?;
+
+            if raw.iter().any(|(other, _)| *other == name) {
+                return Err(schema_error!("struct `{}` is defined more than once", name));
+            }
+
+            raw.push((name, fields));
+            rest = next;
+        }
+
+        let names = raw.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
+        let mut parsed = Vec::new();
+
+        for (name, fields) in &raw {
+            let mut typed = Vec::new();
+
+            for (field, ty) in fields {
+                let (ty, rest) = parse_type(ty, &names)?;
+
+                if !rest.is_empty() {
+                    return Err(schema_error!("unexpected `{}` in `{}.{}`", rest[0], name, field));
+                }
+
+                typed.push((field.clone(), ty));
+            }
+
+            parsed.push(typed);
+        }
+
+        let mut layouts = vec![None; raw.len()];
+
+        for i in 0..raw.len() {
+            struct_layout(i, &parsed, &names, &mut layouts, &mut Vec::new())?;
+        }
+
+        let structs = raw.into_iter()
+            .zip(parsed)
+            .zip(layouts)
+            .map(|(((name, _), fields), layout)| {
+                let (size, align, offsets) = layout.unwrap();
+
+                Struct {
+                    name,
+                    size,
+                    align,
+                    fields: fields.into_iter()
+                        .zip(offsets)
+                        .map(|((name, ty), offset)| Field { name, ty, offset })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        let schema = Self { structs };
+
+        for s in &schema.structs {
+            for field in &s.fields {
+                schema.check_pointees(&field.ty)?;
+            }
+        }
+
+        Ok(schema)
+    }
+
+    /// The structs in the schema
+    pub fn structs(&self) -> &[Struct] {
+        &self.structs
+    }
+
+    /// Find a struct by name
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.structs.iter().position(|s| s.name == name)
+    }
+
+    /**
+     * The size and alignment of `ty`
+     *
+     * # Panics
+     *
+     * If `ty` is an array that is larger than `isize::MAX`, which can't happen for
+     * the types in the schema, because they are checked when it is parsed
+     */
+    pub fn layout(&self, ty: &Type) -> (usize, usize) {
+        self.checked_layout(ty).expect("the array is too large")
+    }
+
+    /// The size and alignment of `ty`, or `None` if it contains an array that is too large
+    fn checked_layout(&self, ty: &Type) -> Option<(usize, usize)> {
+        Some(match ty {
+            Type::Prim(name) => prim_layout(name),
+            Type::Array(ty, len) => array_layout(self.checked_layout(ty)?, *len)?,
+            Type::Struct(i) => (self.structs[*i].size, self.structs[*i].align),
+            Type::Ptr(delta, _) => rel_ptr_layout(*delta, false),
+            Type::Slice(delta, _) | Type::Str(delta) => rel_ptr_layout(*delta, true),
+        })
+    }
+
+    /// Check that the pointees of the relative pointers in `ty` have a layout
+    fn check_pointees(&self, ty: &Type) -> Result<(), SchemaError> {
+        match ty {
+            Type::Prim(_) | Type::Struct(_) | Type::Str(_) => Ok(()),
+            Type::Array(ty, _) => self.check_pointees(ty),
+            Type::Ptr(_, pointee) | Type::Slice(_, pointee) => match self.checked_layout(pointee) {
+                Some(_) => self.check_pointees(pointee),
+                None => Err(schema_error!("`{}` is too large", self.type_name(pointee))),
+            },
+        }
+    }
+
+    /// The name of `ty`, as it would be written in a schema
+    pub fn type_name(&self, ty: &Type) -> String {
+        fn delta(kind: DeltaKind) -> &'static str {
+            match kind {
+                DeltaKind::I8 => "i8",
+                DeltaKind::I16 => "i16",
+                DeltaKind::I32 => "i32",
+                DeltaKind::I64 => "i64",
+                DeltaKind::Isize => "isize",
+            }
+        }
+
+        match ty {
+            Type::Prim(name) => name.to_string(),
+            Type::Array(ty, len) => format!("[{}; {}]", self.type_name(ty), len),
+            Type::Struct(i) => self.structs[*i].name.clone(),
+            Type::Ptr(kind, ty) => format!("ptr<{}, {}>", delta(*kind), self.type_name(ty)),
+            Type::Slice(kind, ty) => format!("slice<{}, {}>", delta(*kind), self.type_name(ty)),
+            Type::Str(kind) => format!("str<{}>", delta(*kind)),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<String>, SchemaError> {
+    let mut tokens = Vec::new();
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap();
+        let line = line.split("//").next().unwrap();
+        let mut chars = line.char_indices().peekable();
+
+        while let Some((start, c)) = chars.next() {
+            if c.is_whitespace() {
+                continue;
+            }
+
+            if "{}<>[];,:".contains(c) {
+                tokens.push(c.to_string());
+            } else if c.is_alphanumeric() || c == '_' {
+                let mut end = start + c.len_utf8();
+
+                while let Some(&(i, c)) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' {
+                        end = i + c.len_utf8();
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+
+                tokens.push(line[start..end].to_string());
+            } else {
+                return Err(schema_error!("unexpected character `{}`", c));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn expect<'a>(tokens: &'a [String], token: &str) -> Result<&'a [String], SchemaError> {
+    match tokens.first() {
+        Some(next) if next == token => Ok(&tokens[1..]),
+        Some(next) => Err(schema_error!("expected `{}`, found `{}`", token, next)),
+        None => Err(schema_error!("expected `{}`, found the end of the schema", token)),
+    }
+}
+
+fn ident(tokens: &[String]) -> Result<(&str, &[String]), SchemaError> {
+    match tokens.first() {
+        Some(next) if next.starts_with(|c: char| c.is_alphabetic() || c == '_') => Ok((next, &tokens[1..])),
+        Some(next) => Err(schema_error!("expected a name, found `{}`", next)),
+        None => Err(schema_error!("expected a name, found the end of the schema")),
+    }
+}
+
+/// A struct with the tokens of each field's type
+type RawStruct<'a> = (String, Vec<(String, &'a [String])>, &'a [String]);
+
+fn parse_struct(tokens: &[String]) -> Result<RawStruct<'_>, SchemaError> {
+    let tokens = expect(tokens, "struct")?;
+    let (name, tokens) = ident(tokens)?;
+    let mut tokens = expect(tokens, "{")?;
+    let mut fields = Vec::new();
+
+    loop {
+        if let Ok(rest) = expect(tokens, "}") {
+            return Ok((name.to_string(), fields, rest));
+        }
+
+        let (field, rest) = ident(tokens)?;
+        let rest = expect(rest, ":")?;
+
+        // the type ends at the first `,` or `}` outside of brackets
+        let mut depth = 0_usize;
+        let end = rest.iter().position(|token| {
+            match &token[..] {
+                "<" | "[" => depth += 1,
+                ">" | "]" => depth = depth.saturating_sub(1),
+                "," | "}" if depth == 0 => return true,
+                _ => (),
+            }
+
+            false
+        }).ok_or_else(|| schema_error!("struct `{}` is missing a `}}`", name))?;
+
+        if fields.iter().any(|(other, _)| other == field) {
+            return Err(schema_error!("field `{}.{}` is defined more than once", name, field));
+        }
+
+        fields.push((field.to_string(), &rest[..end]));
+        tokens = if rest[end] == "," { &rest[end + 1..] } else { &rest[end..] };
+    }
+}
+
+fn parse_delta(tokens: &[String]) -> Result<(DeltaKind, &[String]), SchemaError> {
+    let (name, rest) = ident(tokens)?;
+    let kind = match name {
+        "i8" => DeltaKind::I8,
+        "i16" => DeltaKind::I16,
+        "i32" => DeltaKind::I32,
+        "i64" => DeltaKind::I64,
+        "isize" => DeltaKind::Isize,
+        _ => return Err(schema_error!("`{}` is not a supported offset type", name)),
+    };
+
+    Ok((kind, rest))
+}
+
+fn parse_type<'a>(tokens: &'a [String], names: &[String]) -> Result<(Type, &'a [String]), SchemaError> {
+    if let Ok(rest) = expect(tokens, "[") {
+        let (ty, rest) = parse_type(rest, names)?;
+        let rest = expect(rest, ";")?;
+        let len = rest.first().ok_or_else(|| schema_error!("expected an array length"))?;
+        let len = len.parse().map_err(|_| schema_error!("`{}` is not an array length", len))?;
+
+        return Ok((Type::Array(Box::new(ty), len), expect(&rest[1..], "]")?));
+    }
+
+    let (name, rest) = ident(tokens)?;
+
+    match name {
+        "ptr" | "slice" => {
+            let (delta, rest) = parse_delta(expect(rest, "<")?)?;
+            let (ty, rest) = parse_type(expect(rest, ",")?, names)?;
+            let rest = expect(rest, ">")?;
+
+            if name == "ptr" {
+                Ok((Type::Ptr(delta, Box::new(ty)), rest))
+            } else {
+                Ok((Type::Slice(delta, Box::new(ty)), rest))
+            }
+        },
+        "str" => {
+            let (delta, rest) = parse_delta(expect(rest, "<")?)?;
+
+            Ok((Type::Str(delta), expect(rest, ">")?))
+        },
+        _ => {
+            if let Some(&(prim, _)) = PRIMS.iter().find(|(prim, _)| *prim == name) {
+                Ok((Type::Prim(prim), rest))
+            } else if let Some(i) = names.iter().position(|other| other == name) {
+                Ok((Type::Struct(i), rest))
+            } else {
+                Err(schema_error!("unknown type `{}`", name))
+            }
+        },
+    }
+}
+
+/// The size, alignment and field offsets of a struct
+type Layout = (usize, usize, Vec<usize>);
+
+fn struct_layout(
+    i: usize,
+    parsed: &[Vec<(String, Type)>],
+    names: &[String],
+    layouts: &mut Vec<Option<Layout>>,
+    stack: &mut Vec<usize>,
+) -> Result<(usize, usize), SchemaError> {
+    if let Some((size, align, _)) = layouts[i] {
+        return Ok((size, align));
+    }
+
+    if stack.contains(&i) {
+        return Err(schema_error!("struct `{}` contains itself, use a relative pointer instead", names[i]));
+    }
+
+    stack.push(i);
+
+    let mut size = 0;
+    let mut align = 1;
+    let mut offsets = Vec::new();
+
+    for (_, ty) in &parsed[i] {
+        let too_large = || schema_error!("struct `{}` is too large", names[i]);
+        let (field_size, field_align) = type_layout(ty, parsed, names, layouts, stack)?;
+
+        size = align_up(size, field_align).ok_or_else(too_large)?;
+        offsets.push(size);
+        size = size.checked_add(field_size).ok_or_else(too_large)?;
+        align = align.max(field_align);
+    }
+
+    size = align_up(size, align)
+        .filter(|&size| size <= isize::MAX as usize)
+        .ok_or_else(|| schema_error!("struct `{}` is too large", names[i]))?;
+    stack.pop();
+    layouts[i] = Some((size, align, offsets));
+
+    Ok((size, align))
+}
+
+fn type_layout(
+    ty: &Type,
+    parsed: &[Vec<(String, Type)>],
+    names: &[String],
+    layouts: &mut Vec<Option<Layout>>,
+    stack: &mut Vec<usize>,
+) -> Result<(usize, usize), SchemaError> {
+    Ok(match ty {
+        Type::Array(ty, len) => {
+            let layout = type_layout(ty, parsed, names, layouts, stack)?;
+
+            array_layout(layout, *len).ok_or_else(|| {
+                schema_error!("an array of {} elements in struct `{}` is too large", len, names[*stack.last().unwrap()])
+            })?
+        },
+        Type::Struct(i) => struct_layout(*i, parsed, names, layouts, stack)?,
+        Type::Prim(name) => prim_layout(name),
+        Type::Ptr(delta, _) => rel_ptr_layout(*delta, false),
+        Type::Slice(delta, _) | Type::Str(delta) => rel_ptr_layout(*delta, true),
+    })
+}
+
+/// A problem found while inspecting an image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IssueKind {
+    /// A relative pointer points (partially) out of the image
+    OutOfBounds,
+    /// A relative pointer points to a position that is not aligned for its pointee
+    Misaligned,
+    /// A value partially overlaps another value
+    Overlapping,
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            IssueKind::OutOfBounds => "out of bounds",
+            IssueKind::Misaligned => "misaligned",
+            IssueKind::Overlapping => "overlapping",
+        })
+    }
+}
+
+/// A problem found while inspecting an image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    /// What went wrong
+    pub kind: IssueKind,
+    /// The position of the relative pointer, or of the overlapping value
+    pub pos: usize,
+    /// A description of the problem
+    pub message: String,
+}
+
+/// A value that was reached while inspecting an image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Object {
+    /// The position of the value in the image
+    pub pos: usize,
+    /// The size of the value in bytes
+    pub len: usize,
+    /// The name of the type of the value
+    pub ty: String,
+}
+
+/// A relative pointer that was followed while inspecting an image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge {
+    /// The index of the object that contains the relative pointer
+    pub from: usize,
+    /// The position of the relative pointer in the image
+    pub pos: usize,
+    /// The path to the relative pointer, like `Node.next`
+    pub path: String,
+    /// The position of the target in the image, which may be out of the image
+    pub target: isize,
+    /// The index of the object that is pointed to, if it is valid
+    pub to: Option<usize>,
+    /// The problem with the relative pointer, if any
+    pub issue: Option<IssueKind>,
+}
+
+/// The values, relative pointers and problems found while inspecting an image
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Every value reached from the root, the root is first
+    pub objects: Vec<Object>,
+    /// Every non-null relative pointer that was followed
+    pub edges: Vec<Edge>,
+    /// Every problem that was found
+    pub issues: Vec<Issue>,
+    /// The null relative pointers, by position
+    pub nulls: Vec<(usize, String)>,
+}
+
+struct Inspector<'a> {
+    image: &'a [u8],
+    schema: &'a Schema,
+    report: Report,
+    objects: HashMap<(usize, String), usize>,
+    queue: Vec<(usize, Type, usize, String)>,
+}
+
+impl Inspector<'_> {
+    /// Add the value of type `ty` at `target`, or flag the edge if it is invalid
+    fn object(&mut self, target: isize, ty: &Type, count: usize) -> Result<usize, IssueKind> {
+        let (size, align) = self.schema.layout(ty);
+        let len = size.checked_mul(count).ok_or(IssueKind::OutOfBounds)?;
+
+        if target < 0 || (target as usize).checked_add(len).map_or(true, |end| end > self.image.len()) {
+            return Err(IssueKind::OutOfBounds);
+        }
+
+        let pos = target as usize;
+
+        if pos % align != 0 {
+            return Err(IssueKind::Misaligned);
+        }
+
+        let name = if count == 1 {
+            self.schema.type_name(ty)
+        } else {
+            format!("[{}; {}]", self.schema.type_name(ty), count)
+        };
+
+        if let Some(&index) = self.objects.get(&(pos, name.clone())) {
+            return Ok(index);
+        }
+
+        let index = self.report.objects.len();
+
+        self.objects.insert((pos, name.clone()), index);
+        self.report.objects.push(Object { pos, len, ty: name.clone() });
+
+        // zero sized values don't contain any relative pointers
+        for i in 0..if size == 0 { 0 } else { count } {
+            let path = if count == 1 { name.clone() } else { format!("{}[{}]", name, i) };
+            self.queue.push((pos + i * size, ty.clone(), index, path));
+        }
+
+        Ok(index)
+    }
+
+    fn walk(&mut self, pos: usize, ty: &Type, from: usize, path: &str) {
+        match ty {
+            Type::Prim(_) => (),
+            Type::Array(ty, len) => {
+                let (size, _) = self.schema.layout(ty);
+
+                for i in 0..*len {
+                    self.walk(pos + i * size, ty, from, &format!("{}[{}]", path, i));
+                }
+            },
+            Type::Struct(i) => {
+                let schema = self.schema;
+
+                for field in &schema.structs[*i].fields {
+                    self.walk(pos + field.offset, &field.ty, from, &format!("{}.{}", path, field.name));
+                }
+            },
+            Type::Ptr(delta, pointee) => self.pointer(pos, *delta, false, pointee, from, path),
+            Type::Slice(delta, pointee) => self.pointer(pos, *delta, true, pointee, from, path),
+            Type::Str(delta) => self.pointer(pos, *delta, true, &Type::Prim("u8"), from, path),
+        }
+    }
+
+    fn pointer(&mut self, pos: usize, delta: DeltaKind, fat: bool, pointee: &Type, from: usize, path: &str) {
+        let (offset, count) = read_rel_ptr(self.image, pos, delta, fat);
+
+        if offset == 0 {
+            self.report.nulls.push((pos, path.to_string()));
+            return;
+        }
+
+        let target = (pos as isize).wrapping_add(offset);
+        let (to, issue) = match self.object(target, pointee, count) {
+            Ok(to) => (Some(to), None),
+            Err(issue) => {
+                self.report.issues.push(Issue {
+                    kind: issue,
+                    pos,
+                    message: format!("{} at {:#x} points to {:#x}, which is {}", path, pos, target, issue),
+                });
+
+                (None, Some(issue))
+            },
+        };
+
+        self.report.edges.push(Edge { from, pos, path: path.to_string(), target, to, issue });
+    }
+
+    fn overlaps(&mut self) {
+        let mut objects = self.report.objects.iter().collect::<Vec<_>>();
+        objects.sort_by_key(|object| (object.pos, !object.len));
+
+        for (i, object) in objects.iter().enumerate() {
+            let end = object.pos + object.len;
+
+            for other in &objects[i + 1..] {
+                if other.pos >= end {
+                    break;
+                }
+
+                // values that are inside of other values, like fields, are fine
+                if other.pos + other.len > end {
+                    self.report.issues.push(Issue {
+                        kind: IssueKind::Overlapping,
+                        pos: other.pos,
+                        message: format!(
+                            "{} at {:#x} overlaps {} at {:#x}",
+                            other.ty, other.pos, object.ty, object.pos
+                        ),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/**
+ * Inspect `image`, starting from the value of type `root_type` at `root`
+ *
+ * Every relative pointer that can be reached from the root is followed,
+ * see the module docs
+ */
+pub fn inspect(image: &[u8], root: usize, schema: &Schema, root_type: &str) -> Result<Report, SchemaError> {
+    let index = schema.find(root_type)
+        .ok_or_else(|| schema_error!("unknown type `{}`", root_type))?;
+
+    let mut inspector = Inspector {
+        image,
+        schema,
+        report: Report::default(),
+        objects: HashMap::new(),
+        queue: Vec::new(),
+    };
+
+    if let Err(issue) = inspector.object(root as isize, &Type::Struct(index), 1) {
+        inspector.report.issues.push(Issue {
+            kind: issue,
+            pos: root,
+            message: format!("the root at {:#x} is {}", root, issue),
+        });
+    }
+
+    while let Some((pos, ty, from, path)) = inspector.queue.pop() {
+        inspector.walk(pos, &ty, from, &path);
+    }
+
+    inspector.overlaps();
+    inspector.report.edges.sort_by_key(|edge| edge.pos);
+    inspector.report.issues.sort_by_key(|issue| issue.pos);
+
+    Ok(inspector.report)
+}
+
+impl Report {
+    /// Check if there were any problems with the image
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /**
+     * Write an annotated hexdump of the parts of `image` that were reached,
+     * each line is annotated with the values that start on it, the relative pointers
+     * in it, and the problems that were found
+     */
+    pub fn write_hexdump<W: Write>(&self, image: &[u8], mut out: W) -> io::Result<()> {
+        let mut notes = Vec::new();
+
+        for object in &self.objects {
+            notes.push((object.pos, format!("{} ({} bytes)", object.ty, object.len)));
+        }
+
+        for edge in &self.edges {
+            notes.push((edge.pos, format!("{} -> {:#x}", edge.path, edge.target)));
+        }
+
+        for (pos, path) in &self.nulls {
+            notes.push((*pos, format!("{} -> null", path)));
+        }
+
+        for issue in &self.issues {
+            notes.push((issue.pos, format!("!! {}", issue.message)));
+        }
+
+        notes.sort_by_key(|&(pos, _)| pos);
+
+        // the parts of the image that are covered by a value or a note, sorted and merged,
+        // so that both can be swept along with the lines instead of searched for each line
+        let mut ranges = self.objects.iter()
+            .map(|object| (object.pos, object.pos.saturating_add(object.len)))
+            .chain(notes.iter().map(|&(pos, _)| (pos, pos.saturating_add(1))))
+            .collect::<Vec<_>>();
+
+        ranges.sort_unstable();
+        ranges.dedup_by(|next, prev| {
+            let overlaps = next.0 <= prev.1;
+
+            if overlaps {
+                prev.1 = prev.1.max(next.1);
+            }
+
+            overlaps
+        });
+
+        let mut ranges = ranges.iter().peekable();
+        let mut notes = notes.iter().peekable();
+        let mut skipped = false;
+
+        for (line, bytes) in image.chunks(16).enumerate() {
+            let start = line * 16;
+            let end = start + bytes.len();
+
+            while ranges.next_if(|&&(_, range_end)| range_end <= start).is_some() {}
+
+            if !matches!(ranges.peek(), Some(&&(range_start, _)) if range_start < end) {
+                if !skipped {
+                    writeln!(out, "*")?;
+                    skipped = true;
+                }
+
+                continue;
+            }
+
+            skipped = false;
+            write!(out, "{:08x} ", start)?;
+
+            for i in 0..16 {
+                if i % 8 == 0 {
+                    write!(out, " ")?;
+                }
+
+                match bytes.get(i) {
+                    Some(byte) => write!(out, "{:02x} ", byte)?,
+                    None => write!(out, "   ")?,
+                }
+            }
+
+            let text = bytes.iter()
+                .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+                .collect::<String>();
+
+            write!(out, " |{:16}|", text)?;
+
+            let mut line_notes = Vec::new();
+
+            while let Some((_, note)) = notes.next_if(|&&(pos, _)| pos < end) {
+                line_notes.push(&note[..]);
+            }
+
+            if !line_notes.is_empty() {
+                write!(out, "  {}", line_notes.join("; "))?;
+            }
+
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Write a Graphviz DOT graph of the values and the relative pointers between them,
+     * invalid relative pointers and overlapping values are red
+     */
+    pub fn write_dot<W: Write>(&self, mut out: W) -> io::Result<()> {
+        let overlapping = self.issues.iter()
+            .filter(|issue| issue.kind == IssueKind::Overlapping)
+            .map(|issue| issue.pos)
+            .collect::<HashSet<_>>();
+
+        writeln!(out, "digraph image {{")?;
+        writeln!(out, "    node [shape=box];")?;
+
+        for (i, object) in self.objects.iter().enumerate() {
+            let color = if overlapping.contains(&object.pos) { ", color=red" } else { "" };
+
+            writeln!(
+                out,
+                "    n{} [label=\"{} @ {:#x}\\n{} bytes\"{}];",
+                i, escape(&object.ty), object.pos, object.len, color
+            )?;
+        }
+
+        for (i, edge) in self.edges.iter().enumerate() {
+            let label = escape(&edge.path);
+
+            match (edge.to, edge.issue) {
+                (Some(to), _) => writeln!(out, "    n{} -> n{} [label=\"{}\"];", edge.from, to, label)?,
+                (None, issue) => {
+                    let issue = issue.map_or(String::new(), |issue| issue.to_string());
+
+                    writeln!(
+                        out,
+                        "    bad{} [label=\"{:#x}\\n{}\", color=red, shape=plaintext];",
+                        i, edge.target, issue
+                    )?;
+                    writeln!(out, "    n{} -> bad{} [label=\"{}\", color=red];", edge.from, i, label)?;
+                },
+            }
+        }
+
+        writeln!(out, "}}")
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
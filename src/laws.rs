@@ -0,0 +1,265 @@
+/*!
+ * Property checks for the laws that `Delta`, `Nullable` and `IntegerDelta` must follow
+ *
+ * The safety of `RelPtr` depends on these laws, so custom implementations should be tested
+ * with `check_delta_laws`, and `check_nullable_laws` or `check_integer_delta_laws` if they apply.
+ * Each check tries many pseudo-random cases (the same ones on every run), and panics with a
+ * description of the law and the failing case if a law is broken, so they can be called
+ * directly from a `#[test]`.
+ *
+ * ```rust
+ * # fn main() {
+ * use rel_ptr::laws::*;
+ *
+ * check_delta_laws::<i16>();
+ * check_nullable_laws::<i16>();
+ * check_integer_delta_laws::<i16>();
+ * # }
+ * ```
+ *
+ * The single case checks, like `check_delta_pair`, are used by the fuzz targets in `fuzz/`
+ */
+
+use super::{Delta, Nullable, IntegerDelta};
+
+/// The number of cases that each check tries
+pub const CASES: usize = 1024;
+
+/// The number of bits in an `isize`
+const ISIZE_BITS: u32 = (std::mem::size_of::<isize>() * 8) as u32;
+
+/// The size of the buffer that pointers are picked from
+const BUFFER: usize = 4096;
+
+/// A xorshift generator, so that the checks don't need any dependencies
+struct Rng(u64);
+
+impl Rng {
+    fn new() -> Self {
+        Rng(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Mostly small numbers, with some of every size, and the edge cases
+    fn isize(&mut self) -> isize {
+        let value = self.next();
+
+        match value % 8 {
+            0 => isize::MIN,
+            1 => isize::MAX,
+            2 => (value >> 3) as isize,
+            _ => ((value >> 3) as isize) >> (value >> 58),
+        }
+    }
+}
+
+/**
+ * Check the `Delta` laws for the pointers at `a` and `b` in `buffer`
+ *
+ * * If `D::sub(a, b)` is `Ok(d)`, then `D::add(d, b) == a`
+ *   and `D::sub_unchecked(a, b) == d`
+ *
+ * # Panics
+ *
+ * If a law is broken, or if `a` or `b` is more than one past the end of `buffer`
+ */
+pub fn check_delta_pair<D: Delta>(buffer: &[u8], a: usize, b: usize) {
+    assert!(a <= buffer.len() && b <= buffer.len(), "the pointers must be in the buffer");
+
+    let base = buffer.as_ptr();
+    let (pa, pb) = unsafe { (base.add(a) as *mut u8, base.add(b) as *mut u8) };
+
+    if let Ok(d) = D::sub(pa, pb) {
+        unsafe {
+            assert!(
+                D::add(d, pb) == pa,
+                "Delta law broken: add(sub(a, b), b) != a, for a = b + {}",
+                a as isize - b as isize
+            );
+
+            assert!(
+                D::sub_unchecked(pa, pb) == d,
+                "Delta law broken: sub_unchecked(a, b) != sub(a, b), for a = b + {}",
+                a as isize - b as isize
+            );
+        }
+    }
+}
+
+/**
+ * Check the `Nullable` laws for the pointer at `a` in `buffer`
+ *
+ * * `D::add(D::NULL, a) == a`
+ * * `D::sub(a, a) == Ok(D::NULL)`
+ * * `D::sub_unchecked(a, a) == D::NULL`
+ *
+ * # Panics
+ *
+ * If a law is broken, or if `a` is more than one past the end of `buffer`
+ */
+pub fn check_nullable_at<D: Nullable>(buffer: &[u8], a: usize) {
+    assert!(a <= buffer.len(), "the pointer must be in the buffer");
+
+    let pa = unsafe { buffer.as_ptr().add(a) as *mut u8 };
+
+    unsafe {
+        assert!(D::add(D::NULL, pa) == pa, "Nullable law broken: add(NULL, a) != a");
+        assert!(D::sub_unchecked(pa, pa) == D::NULL, "Nullable law broken: sub_unchecked(a, a) != NULL");
+    }
+
+    assert!(
+        matches!(D::sub(pa, pa), Ok(d) if d == D::NULL),
+        "Nullable law broken: sub(a, a) != Ok(NULL)"
+    );
+}
+
+/**
+ * Check the `IntegerDelta` laws for the offset `del`
+ *
+ * * `D::from_isize(del)` is `Ok` if and only if `del` fits in `D::BITS` signed bits,
+ *   except that it may be `Err` for `0` (like for `NonZero*`)
+ * * If `D::from_isize(del)` is `Ok(d)`, then `d.to_isize() == del`, `d.try_to_isize() == Ok(del)`
+ *   and `D::from_isize(d.to_isize()) == Ok(d)`
+ *
+ * # Panics
+ *
+ * If a law is broken
+ */
+pub fn check_integer_delta_value<D: IntegerDelta>(del: isize) {
+    let fits = D::BITS >= ISIZE_BITS || {
+        let max = (1_isize << (D::BITS - 1)) - 1;
+        -max - 1 <= del && del <= max
+    };
+
+    match D::from_isize(del) {
+        Ok(d) => {
+            assert!(fits, "IntegerDelta law broken: from_isize({}) is Ok, but it doesn't fit in {} bits", del, D::BITS);
+            assert!(d.to_isize() == del, "IntegerDelta law broken: from_isize({}).to_isize() != {}", del, del);
+            assert!(d.try_to_isize() == Ok(del), "IntegerDelta law broken: from_isize({}).try_to_isize() != Ok({})", del, del);
+            assert!(
+                matches!(D::from_isize(d.to_isize()), Ok(e) if e == d),
+                "IntegerDelta law broken: from_isize(d.to_isize()) != Ok(d) for {}", del
+            );
+        },
+        Err(_) => assert!(
+            !fits || del == 0,
+            "IntegerDelta law broken: from_isize({}) is Err, but it fits in {} bits", del, D::BITS
+        ),
+    }
+}
+
+/**
+ * Check the `IntegerDelta` laws that relate it to `Delta`,
+ * for the pointers at `a` and `b` in `buffer`
+ *
+ * * `D::sub(a, b)` is the same as `D::from_isize(a - b)`
+ * * `D::add(d, b) == b.offset(d.to_isize())`
+ *
+ * # Panics
+ *
+ * If a law is broken, or if `a` or `b` is more than one past the end of `buffer`
+ */
+pub fn check_integer_delta_pair<D: IntegerDelta>(buffer: &[u8], a: usize, b: usize) {
+    assert!(a <= buffer.len() && b <= buffer.len(), "the pointers must be in the buffer");
+
+    let base = buffer.as_ptr();
+    let (pa, pb) = unsafe { (base.add(a) as *mut u8, base.add(b) as *mut u8) };
+    let del = a as isize - b as isize;
+
+    match (D::sub(pa, pb), D::from_isize(del)) {
+        (Ok(d), Ok(e)) => {
+            assert!(d == e, "IntegerDelta law broken: sub(a, b) != from_isize(a - b), for a = b + {}", del);
+            assert!(
+                unsafe { D::add(d, pb) == pb.offset(d.to_isize()) },
+                "IntegerDelta law broken: add(d, b) != b.offset(d.to_isize()), for a = b + {}", del
+            );
+        },
+        (Err(_), Err(_)) => (),
+        _ => panic!("IntegerDelta law broken: sub(a, b) and from_isize(a - b) disagree, for a = b + {}", del),
+    }
+}
+
+/// Pick a pair of positions in the buffer, sometimes equal, sometimes at the ends
+fn positions(rng: &mut Rng) -> (usize, usize) {
+    let a = rng.next() as usize % (BUFFER + 1);
+
+    let b = match rng.next() % 4 {
+        0 => a,
+        1 => [0, BUFFER][rng.next() as usize % 2],
+        _ => rng.next() as usize % (BUFFER + 1),
+    };
+
+    (a, b)
+}
+
+/**
+ * Check the `Delta` laws for many pairs of pointers, see `check_delta_pair`
+ *
+ * # Panics
+ *
+ * If a law is broken
+ */
+pub fn check_delta_laws<D: Delta>() {
+    let buffer = [0_u8; BUFFER];
+    let mut rng = Rng::new();
+
+    for _ in 0..CASES {
+        let (a, b) = positions(&mut rng);
+        check_delta_pair::<D>(&buffer, a, b);
+    }
+}
+
+/**
+ * Check the `Nullable` laws for many pointers, see `check_nullable_at`
+ *
+ * # Panics
+ *
+ * If a law is broken
+ */
+pub fn check_nullable_laws<D: Nullable>() {
+    let buffer = [0_u8; BUFFER];
+    let mut rng = Rng::new();
+
+    for _ in 0..CASES {
+        let (a, _) = positions(&mut rng);
+        check_nullable_at::<D>(&buffer, a);
+    }
+}
+
+/**
+ * Check the `IntegerDelta` laws for many offsets and pairs of pointers,
+ * see `check_integer_delta_value` and `check_integer_delta_pair`
+ *
+ * # Panics
+ *
+ * If a law is broken
+ */
+pub fn check_integer_delta_laws<D: IntegerDelta>() {
+    let buffer = [0_u8; BUFFER];
+    let mut rng = Rng::new();
+
+    for del in [0, 1, -1, isize::MIN, isize::MAX].iter() {
+        check_integer_delta_value::<D>(*del);
+    }
+
+    for bits in 1..=D::BITS.min(ISIZE_BITS - 1) {
+        let max = (1_isize << (bits - 1)) - 1;
+
+        for del in [max, max + 1, -max - 1, -max - 2].iter() {
+            check_integer_delta_value::<D>(*del);
+        }
+    }
+
+    for _ in 0..CASES {
+        check_integer_delta_value::<D>(rng.isize());
+
+        let (a, b) = positions(&mut rng);
+        check_integer_delta_pair::<D>(&buffer, a, b);
+    }
+}
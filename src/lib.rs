@@ -28,6 +28,23 @@
 
     with nightly you get the ability to use trait objects with relative pointers
 
+    ### serde
+
+    with serde you get the `serde` module, to serialize self-referential types
+
+    ### mmap
+
+    with mmap you get the `mmap` module, to load relocatable images from files without copying them (unix only)
+
+    ### shm
+
+    with shm you get the `shm` module, to share structures linked with relative pointers between processes (Linux only)
+
+    ### inspect
+
+    with inspect you get the `inspect` module, and the `rel-ptr-inspect` binary, to print an annotated hexdump
+    and a Graphviz graph of a relocatable image, and find corrupt relative pointers
+
     ## Example
 
     take the memory segment below
@@ -47,7 +64,7 @@
     1) it only took 1 byte to point to another value,
     2) a relative pointer cannot access all memory, only memory near it
     3) if both the relative pointer and the pointee move together,
-    then the relative pointer will not be invalidated
+       then the relative pointer will not be invalidated
 
     The third point is what makes moveable self-referential structs possible
 
@@ -130,20 +147,75 @@ mod tests;
 mod nightly;
 
 mod traits;
+mod tagged;
+mod chain;
+mod project;
+mod slice;
+mod auto;
+mod trace;
+mod rebase;
+mod clone;
+mod target;
+mod arith;
+mod convert;
+mod copy;
+mod access;
+mod func;
+#[cfg(not(feature = "no_std"))]
+mod graph;
+
+pub mod varint;
+pub mod debug;
+pub mod laws;
+
+#[cfg(feature = "serde")]
+pub mod serde;
+
+#[cfg(all(feature = "mmap", unix, not(feature = "no_std")))]
+pub mod mmap;
+
+#[cfg(all(feature = "shm", target_os = "linux", not(feature = "no_std")))]
+pub mod shm;
+
+#[cfg(all(feature = "inspect", not(feature = "no_std")))]
+pub mod inspect;
+
 mod error;
 mod fmt;
 
 mod unreachable;
 
+#[cfg(all(feature = "mmap", feature = "no_std"))]
+compile_error!("the `mmap` feature requires `std`, so it can't be used with `no_std`");
+
+#[cfg(all(feature = "shm", feature = "no_std"))]
+compile_error!("the `shm` feature requires `std`, so it can't be used with `no_std`");
+
+#[cfg(all(feature = "inspect", feature = "no_std"))]
+compile_error!("the `inspect` feature requires `std`, so it can't be used with `no_std`");
+
 #[cfg(feature = "nightly")]
 pub use self::nightly::*;
 pub use self::traits::*;
 pub use self::error::*;
+pub use self::tagged::*;
+pub use self::chain::*;
+pub use self::auto::*;
+pub use self::trace::*;
+pub use self::rebase::*;
+pub use self::clone::*;
+pub use self::target::*;
+pub use self::convert::*;
+pub use self::access::*;
+pub use self::func::*;
+#[cfg(not(feature = "no_std"))]
+pub use self::graph::*;
 
 use core::mem::MaybeUninit;
 
 use crate::unreachable::UncheckedOptionExt as _;
 
+use std::convert::TryFrom;
 use std::marker::PhantomData;
 use std::ptr::NonNull;
 use core::num::*;
@@ -159,15 +231,7 @@ macro_rules! impl_delta_zeroable {
                     None => return Err(IntegerDeltaError(IntegerDeltaErrorImpl::Sub(a as usize, b as usize)))
                 };
 
-                if std::mem::size_of::<Self>() < std::mem::size_of::<isize>() && (
-                    (Self::min_value() as isize) > del ||
-                    (Self::max_value() as isize) < del
-                )
-                {
-                    Err(IntegerDeltaError(IntegerDeltaErrorImpl::Conversion(del)))
-                } else {
-                    Ok(del as _)
-                }
+                Self::from_isize(del)
             }
 
             unsafe fn sub_unchecked(a: *mut u8, b: *mut u8) -> Self {
@@ -177,6 +241,37 @@ macro_rules! impl_delta_zeroable {
             unsafe fn add(self, a: *const u8) -> *mut u8 {
                 <*const u8>::offset(a, self as isize) as *mut u8
             }
+
+            #[inline]
+            fn byte_offset(self) -> Option<isize> {
+                self.try_to_isize().ok()
+            }
+        }
+
+        unsafe impl IntegerDelta for $type {
+            const BITS: u32 = (std::mem::size_of::<Self>() * 8) as u32;
+
+            #[inline]
+            fn to_isize(self) -> isize {
+                self.try_to_isize().expect("the offset doesn't fit in an `isize`")
+            }
+
+            #[inline]
+            fn try_to_isize(self) -> Result<isize, IntegerDeltaError> {
+                isize::try_from(self).map_err(|_| IntegerDeltaError(IntegerDeltaErrorImpl::Truncated(self as i128)))
+            }
+
+            fn from_isize(del: isize) -> Result<Self, IntegerDeltaError> {
+                if std::mem::size_of::<Self>() < std::mem::size_of::<isize>() && (
+                    (Self::MIN as isize) > del ||
+                    (Self::MAX as isize) < del
+                )
+                {
+                    Err(IntegerDeltaError(IntegerDeltaErrorImpl::Conversion(del)))
+                } else {
+                    Ok(del as _)
+                }
+            }
         }
 
         impl Nullable for $type {
@@ -195,20 +290,10 @@ macro_rules! impl_delta_nonzero {
             fn sub(a: *mut u8, b: *mut u8) -> Result<Self, Self::Error> {
                 let del = match isize::checked_sub(a as usize as _, b as usize as _) {
                     None => return Err(IntegerDeltaError(IntegerDeltaErrorImpl::Sub(a as usize, b as usize))),
-                    Some(0) => return Err(IntegerDeltaError(IntegerDeltaErrorImpl::InvalidNonZero)),
                     Some(del) => del,
                 };
 
-                if std::mem::size_of::<Self>() < std::mem::size_of::<isize>() && (
-                    ($base::min_value() as isize) > del ||
-                    ($base::max_value() as isize) < del
-                )
-                {
-                    Err(IntegerDeltaError(IntegerDeltaErrorImpl::Conversion(del)))
-                } else {
-                    // 0 case was checked in match before hand, so this is guarenteed ot be non zero
-                    unsafe { Ok(Self::new_unchecked(del as _)) }
-                }
+                Self::from_isize(del)
             }
 
             unsafe fn sub_unchecked(a: *mut u8, b: *mut u8) -> Self {
@@ -218,6 +303,40 @@ macro_rules! impl_delta_nonzero {
             unsafe fn add(self, a: *const u8) -> *mut u8 {
                 <*mut u8>::offset(a as _, self.get() as isize) as *mut u8
             }
+
+            #[inline]
+            fn byte_offset(self) -> Option<isize> {
+                self.try_to_isize().ok()
+            }
+        }
+
+        unsafe impl IntegerDelta for $type {
+            const BITS: u32 = (std::mem::size_of::<Self>() * 8) as u32;
+
+            #[inline]
+            fn to_isize(self) -> isize {
+                self.try_to_isize().expect("the offset doesn't fit in an `isize`")
+            }
+
+            #[inline]
+            fn try_to_isize(self) -> Result<isize, IntegerDeltaError> {
+                isize::try_from(self.get()).map_err(|_| IntegerDeltaError(IntegerDeltaErrorImpl::Truncated(self.get() as i128)))
+            }
+
+            fn from_isize(del: isize) -> Result<Self, IntegerDeltaError> {
+                if del == 0 {
+                    Err(IntegerDeltaError(IntegerDeltaErrorImpl::InvalidNonZero))
+                } else if std::mem::size_of::<Self>() < std::mem::size_of::<isize>() && (
+                    ($base::MIN as isize) > del ||
+                    ($base::MAX as isize) < del
+                )
+                {
+                    Err(IntegerDeltaError(IntegerDeltaErrorImpl::Conversion(del)))
+                } else {
+                    // 0 case was checked before hand, so this is guarenteed ot be non zero
+                    unsafe { Ok(Self::new_unchecked(del as _)) }
+                }
+            }
         }
     )*};
 }
@@ -268,7 +387,16 @@ fn nn_to_ptr<T: ?Sized>(nn: Ptr<T>) -> *mut T {
  * This is because when `Base` drops, all of the fields are moved to align them. So the offset between the `String` in
  * unsafe thing and the `RelPtr<String>` in `UnsafeThing` could be changed. This will result in UB if you try to access
  * String inside of `UnsafeThing` even if you enforce drop order!
+ *
+ * # Threads
+ *
+ * `RelPtr` is neither `Send` nor `Sync`, like `*mut T`, because nothing says whether the target
+ * is inside of the value that holds the relative pointer, or somewhere that can't be used from
+ * another thread, or whether it is meant to be shared or mutated. To move self-referential types
+ * between threads, use `RelMut` or `RelConst` for their relative pointers instead, which are
+ * `Send` and `Sync` like `&mut T` and `&T`, see `RelMut` for the details.
 */
+#[repr(C)]
 pub struct RelPtr<T: ?Sized + MetaData, I: Delta = isize>(I, MaybeUninit<T::Data>, PhantomData<*mut T>);
 
 // Ergonomics and ptr like impls
@@ -280,6 +408,8 @@ impl<T: ?Sized + MetaData, I: Delta> Clone for RelPtr<T, I> {
     }
 }
 
+/// Relative pointers are equal only if they are the same relative pointer,
+/// use `RelPtr::ptr_eq_target` or `TargetKey` to compare their targets
 impl<T: ?Sized + MetaData, I: Delta> Eq for RelPtr<T, I> {}
 impl<T: ?Sized + MetaData, I: Delta> PartialEq for RelPtr<T, I> {
     fn eq(&self, other: &Self) -> bool {
@@ -311,6 +441,25 @@ impl<T: ?Sized + MetaData, I: Nullable> RelPtr<T, I> {
 }
 
 impl<T: ?Sized + MetaData, I: Delta> RelPtr<T, I> {
+    /// The offset stored in the relative pointer
+    #[inline(always)]
+    pub fn offset(&self) -> I {
+        self.0
+    }
+
+    /**
+     * The metadata stored in the relative pointer,
+     * for example the length of a slice
+     *
+     * # Safety
+     *
+     * The relative pointer must have been successfully set
+     */
+    #[inline(always)]
+    pub unsafe fn metadata(&self) -> T::Data {
+        self.1.assume_init()
+    }
+
     /**
      * Set the offset of a relative pointer,
      * if the offset cannot be calculated using the given
@@ -337,7 +486,7 @@ impl<T: ?Sized + MetaData, I: Delta> RelPtr<T, I> {
      */
     #[inline]
     pub unsafe fn set_unchecked(&mut self, value: *mut T) {
-        self.0 = I::sub_unchecked(value as *mut T as _, self as *mut Self as _);
+        self.0 = I::sub_unchecked(value as _, self as *mut Self as _);
         self.1 = MaybeUninit::new(T::data(&*value));
     }
 
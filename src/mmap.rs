@@ -0,0 +1,533 @@
+/*!
+ * Zero-copy loading of relocatable images, enabled with the `mmap` feature
+ *
+ * This is only available on unix, and requires `std`
+ *
+ * An image is a file that starts with a `Header`, followed by data that uses relative
+ * pointers. Because relative pointers don't depend on where they are in memory, the file
+ * can be mapped into memory and used directly. Before the root of the image is handed out,
+ * every relative pointer that can be reached from the root is checked to point inside
+ * of the image, see `Validate`.
+ *
+ * The image holds the values as they are laid out in memory, so the types in it should
+ * be `#[repr(C)]`. `RelPtr` is `#[repr(C)]`, with the offset followed by the metadata.
+ * The header records the byte order and the size of the offsets, but the metadata of
+ * slices and `str` is a `usize`, so an image can only be used on machines with the same
+ * pointer width as the one that wrote it.
+ */
+
+use super::{RelPtr, RelConst, MetaData, IntegerDelta};
+
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::ptr::NonNull;
+use std::{fmt, io, mem, ptr, slice, str};
+
+/// The magic bytes at the start of every image
+pub const MAGIC: [u8; 8] = *b"RELPTR\0\0";
+
+/// The current version of the image format
+pub const VERSION: u32 = 1;
+
+/// `Header::endian` for little endian images
+pub const LITTLE_ENDIAN: u8 = 0;
+
+/// `Header::endian` for big endian images
+pub const BIG_ENDIAN: u8 = 1;
+
+/// The header at the start of every image
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    /// Must be `MAGIC`
+    pub magic: [u8; 8],
+
+    /// The version of the image format, must be `VERSION`
+    pub version: u32,
+
+    /// The size of the offsets used by relative pointers in the image
+    pub delta_bytes: u8,
+
+    /// The byte order of the image, either `LITTLE_ENDIAN` or `BIG_ENDIAN`
+    pub endian: u8,
+
+    /// Reserved for future use, must be zero
+    pub reserved: [u8; 2],
+
+    /// The position of the root value, from the start of the image
+    pub root: u64,
+}
+
+impl Header {
+    /// Create a header for an image written on this machine, that uses `I` for its offsets
+    pub fn new<I: IntegerDelta>(root: u64) -> Self {
+        Self {
+            magic: MAGIC,
+            version: VERSION,
+            delta_bytes: mem::size_of::<I>() as u8,
+            endian: if cfg!(target_endian = "little") { LITTLE_ENDIAN } else { BIG_ENDIAN },
+            reserved: [0; 2],
+            root,
+        }
+    }
+
+    /// Read the header from the start of an image
+    pub fn read(image: &[u8]) -> Result<Self, MmapError> {
+        if image.len() < mem::size_of::<Self>() {
+            return Err(MmapError::TooSmall);
+        }
+
+        // all bit patterns are valid for `Header`
+        Ok(unsafe { ptr::read_unaligned(image.as_ptr() as *const Self) })
+    }
+
+    /// The bytes of the header, to write at the start of an image
+    pub fn as_bytes(&self) -> &[u8] {
+        // `Header` has no padding, so all of its bytes are initialized
+        unsafe { slice::from_raw_parts(self as *const Self as *const u8, mem::size_of::<Self>()) }
+    }
+
+    /// Check that this header is for an image that can be used on this machine,
+    /// the size of the offsets is checked against each relative pointer while validating
+    pub fn check(&self) -> Result<(), MmapError> {
+        let native = Self::new::<isize>(self.root);
+
+        // the endianness is checked first, because the version is byte swapped
+        // in an image from a machine with the other endianness
+        if self.magic != MAGIC {
+            Err(MmapError::BadMagic)
+        } else if self.endian != native.endian {
+            Err(MmapError::Endianness)
+        } else if self.version != VERSION {
+            Err(MmapError::UnsupportedVersion(self.version))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Errors from loading an image
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum MmapError {
+    /// Failed to open or map the file
+    Io(io::Error),
+
+    /// The image is too small to hold a `Header`
+    TooSmall,
+
+    /// The image does not start with `MAGIC`
+    BadMagic,
+
+    /// The image has a different version than `VERSION`
+    UnsupportedVersion(u32),
+
+    /// The image uses offsets of a different size than a relative pointer in the type being validated
+    DeltaWidth {
+        /// the size of the offsets in the type
+        expected: u8,
+        /// the size of the offsets in the image
+        found: u8,
+    },
+
+    /// The image has a different byte order than this machine
+    Endianness,
+
+    /// A value is not inside of the image, this holds its position in the image
+    OutOfBounds(isize),
+
+    /// A value is not aligned, this holds its position in the image
+    Misaligned(usize),
+
+    /// A value is not valid for its type, this holds its position in the image
+    Invalid(usize),
+}
+
+impl From<io::Error> for MmapError {
+    fn from(err: io::Error) -> Self {
+        MmapError::Io(err)
+    }
+}
+
+impl std::error::Error for MmapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MmapError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MmapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MmapError::Io(err) => write!(f, "Could not map image: {}", err),
+            MmapError::TooSmall => write!(f, "Image is too small to hold a header"),
+            MmapError::BadMagic => write!(f, "Image does not start with the magic bytes"),
+            MmapError::UnsupportedVersion(version) => write!(f, "Image version {} is not supported", version),
+            MmapError::DeltaWidth { expected, found } => write!(
+                f,
+                "Image uses {} byte offsets, but {} byte offsets were expected",
+                found, expected
+            ),
+            MmapError::Endianness => write!(f, "Image has a different byte order"),
+            MmapError::OutOfBounds(pos) => write!(f, "Value at {} is outside of the image", pos),
+            MmapError::Misaligned(pos) => write!(f, "Value at {} is not aligned", pos),
+            MmapError::Invalid(pos) => write!(f, "Value at {} is not valid", pos),
+        }
+    }
+}
+
+/**
+ * Checks values inside of an image
+ *
+ * Each value is only validated once, so cyclic structures can be validated
+ */
+pub struct Validator<'a> {
+    image: &'a [u8],
+    visited: HashSet<(usize, &'static str)>,
+    delta_bytes: Option<u8>,
+}
+
+impl<'a> Validator<'a> {
+    /// Create a new validator for `image`, which accepts relative pointers with any size of offset
+    pub fn new(image: &'a [u8]) -> Self {
+        Self { image, visited: HashSet::new(), delta_bytes: None }
+    }
+
+    /// Create a new validator for `image`, which only accepts relative pointers
+    /// with offsets of `delta_bytes` bytes, like `Header::delta_bytes`
+    pub fn with_delta_bytes(image: &'a [u8], delta_bytes: u8) -> Self {
+        Self { image, visited: HashSet::new(), delta_bytes: Some(delta_bytes) }
+    }
+
+    /// The image that is being validated
+    pub fn image(&self) -> &'a [u8] {
+        self.image
+    }
+
+    /// Check that `len` values of type `T` starting at `pos` are inside of the image and aligned
+    fn check_bounds<T>(&self, pos: usize, len: usize) -> Result<(), MmapError> {
+        let end = mem::size_of::<T>()
+            .checked_mul(len)
+            .and_then(|size| size.checked_add(pos));
+
+        match end {
+            Some(end) if end <= self.image.len() => (),
+            _ => return Err(MmapError::OutOfBounds(pos as isize)),
+        }
+
+        if (self.image.as_ptr() as usize + pos) % mem::align_of::<T>() != 0 {
+            Err(MmapError::Misaligned(pos))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check that there is a valid `T` at `pos`, and that everything
+    /// reachable from it is valid
+    pub fn check<T: Validate>(&mut self, pos: usize) -> Result<(), MmapError> {
+        self.check_bounds::<T>(pos, 1)?;
+
+        if self.visited.insert((pos, std::any::type_name::<T>())) {
+            T::validate(self, pos)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check that there are `len` valid `T`s starting at `pos`, and that
+    /// everything reachable from them is valid
+    pub fn check_slice<T: Validate>(&mut self, pos: usize, len: usize) -> Result<(), MmapError> {
+        self.check_bounds::<T>(pos, len)?;
+
+        // zero sized values are all at the same position, so they only need to be checked once
+        let len = if mem::size_of::<T>() == 0 { len.min(1) } else { len };
+
+        for i in 0..len {
+            self.check::<T>(pos + i * mem::size_of::<T>())?;
+        }
+
+        Ok(())
+    }
+
+    /// Check that there is a valid `str` of `len` bytes at `pos`
+    pub fn check_str(&mut self, pos: usize, len: usize) -> Result<(), MmapError> {
+        self.check_bounds::<u8>(pos, len)?;
+
+        match str::from_utf8(&self.image[pos..pos + len]) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(MmapError::Invalid(pos)),
+        }
+    }
+
+    /**
+     * Get a reference to the value at `pos`
+     *
+     * # Safety
+     *
+     * The value must have been checked to be in bounds and aligned, and
+     * all bit patterns must be valid for `T`
+     */
+    pub unsafe fn get<T>(&self, pos: usize) -> &'a T {
+        &*(self.image.as_ptr().add(pos) as *const T)
+    }
+
+    /**
+     * The position of the target of the relative pointer at `pos`,
+     * or `None` if it is null
+     *
+     * # Safety
+     *
+     * Same as `Validator::get`
+     */
+    unsafe fn target<T: ?Sized + super::MetaData, I: IntegerDelta>(&self, pos: usize) -> Result<Option<usize>, MmapError> {
+        match self.delta_bytes {
+            Some(found) if found as usize != mem::size_of::<I>() => {
+                return Err(MmapError::DeltaWidth { expected: mem::size_of::<I>() as u8, found })
+            },
+            _ => (),
+        }
+
+        let del = match self.get::<RelPtr<T, I>>(pos).0.try_to_isize() {
+            Ok(del) => del,
+            Err(_) => return Err(MmapError::OutOfBounds(isize::MAX)),
+        };
+
+        if del == 0 {
+            return Ok(None);
+        }
+
+        match (pos as isize).checked_add(del) {
+            Some(target) if target >= 0 => Ok(Some(target as usize)),
+            Some(target) => Err(MmapError::OutOfBounds(target)),
+            None => Err(MmapError::OutOfBounds(isize::MAX)),
+        }
+    }
+}
+
+/**
+ * A type that can be checked to be valid inside of an image
+ *
+ * This is implemented for primitives, arrays, and relative pointers that use
+ * the fixed size integer offsets, including `RelConst`, which is the best fit for
+ * images because they are only ever read. To implement it for your own types, check each
+ * field with `Validator::check` at `pos + offset_of!(Self, field)`
+ *
+ * # Safety
+ *
+ * If `validate` returns `Ok`, then the value at `pos` in the image must be valid for `Self`,
+ * and everything reachable from it must be inside of the image and valid
+ */
+pub unsafe trait Validate: Sized {
+    /// Check the value at `pos` in the image, this position is already checked
+    /// to be inside of the image, and aligned for `Self`
+    fn validate(validator: &mut Validator<'_>, pos: usize) -> Result<(), MmapError>;
+}
+
+macro_rules! impl_validate_plain {
+    ($($type:ty),* $(,)?) => {$(
+        unsafe impl Validate for $type {
+            #[inline]
+            fn validate(_: &mut Validator<'_>, _: usize) -> Result<(), MmapError> {
+                Ok(())
+            }
+        }
+    )*};
+}
+
+impl_validate_plain! {
+    (), u8, u16, u32, u64, u128, usize,
+    i8, i16, i32, i64, i128, isize,
+    f32, f64,
+}
+
+unsafe impl Validate for bool {
+    fn validate(validator: &mut Validator<'_>, pos: usize) -> Result<(), MmapError> {
+        if validator.image()[pos] <= 1 {
+            Ok(())
+        } else {
+            Err(MmapError::Invalid(pos))
+        }
+    }
+}
+
+unsafe impl Validate for char {
+    fn validate(validator: &mut Validator<'_>, pos: usize) -> Result<(), MmapError> {
+        let value = unsafe { *validator.get::<u32>(pos) };
+
+        match std::char::from_u32(value) {
+            Some(_) => Ok(()),
+            None => Err(MmapError::Invalid(pos)),
+        }
+    }
+}
+
+unsafe impl<T: Validate, const N: usize> Validate for [T; N] {
+    fn validate(validator: &mut Validator<'_>, pos: usize) -> Result<(), MmapError> {
+        for i in 0..N {
+            T::validate(validator, pos + i * mem::size_of::<T>())?;
+        }
+
+        Ok(())
+    }
+}
+
+macro_rules! impl_validate_rel_ptr {
+    ($($type:ty),* $(,)?) => {$(
+        unsafe impl<T: Validate> Validate for RelPtr<T, $type> {
+            fn validate(validator: &mut Validator<'_>, pos: usize) -> Result<(), MmapError> {
+                match unsafe { validator.target::<T, $type>(pos)? } {
+                    Some(target) => validator.check::<T>(target),
+                    None => Ok(()),
+                }
+            }
+        }
+
+        unsafe impl<T: Validate> Validate for RelPtr<[T], $type> {
+            fn validate(validator: &mut Validator<'_>, pos: usize) -> Result<(), MmapError> {
+                match unsafe { validator.target::<[T], $type>(pos)? } {
+                    Some(target) => {
+                        let len = unsafe { validator.get::<Self>(pos).1.assume_init() };
+
+                        validator.check_slice::<T>(target, len)
+                    },
+                    None => Ok(()),
+                }
+            }
+        }
+
+        unsafe impl Validate for RelPtr<str, $type> {
+            fn validate(validator: &mut Validator<'_>, pos: usize) -> Result<(), MmapError> {
+                match unsafe { validator.target::<str, $type>(pos)? } {
+                    Some(target) => {
+                        let len = unsafe { validator.get::<Self>(pos).1.assume_init() };
+
+                        validator.check_str(target, len)
+                    },
+                    None => Ok(()),
+                }
+            }
+        }
+    )*};
+}
+
+impl_validate_rel_ptr! { i8, i16, i32, i64, isize }
+
+unsafe impl<T: ?Sized + MetaData, I: IntegerDelta> Validate for RelConst<T, I> where RelPtr<T, I>: Validate {
+    #[inline]
+    fn validate(validator: &mut Validator<'_>, pos: usize) -> Result<(), MmapError> {
+        RelPtr::<T, I>::validate(validator, pos)
+    }
+}
+
+/**
+ * Check the header of `image`, and validate everything reachable from its root,
+ * then return the root
+ *
+ * Every relative pointer that is validated must use offsets of the size in the header,
+ * otherwise `MmapError::DeltaWidth` is returned
+ */
+pub fn load_root<T: Validate>(image: &[u8]) -> Result<&T, MmapError> {
+    let header = Header::read(image)?;
+
+    header.check()?;
+
+    let root = usize::try_from(header.root).map_err(|_| MmapError::OutOfBounds(isize::MAX))?;
+    let mut validator = Validator::with_delta_bytes(image, header.delta_bytes);
+
+    validator.check::<T>(root)?;
+
+    // the root was just validated
+    Ok(unsafe { validator.get(root) })
+}
+
+/**
+ * A file that is mapped read-only into memory
+ *
+ * ```rust,no_run
+ * # fn main() -> Result<(), rel_ptr::mmap::MmapError> {
+ * use rel_ptr::RelPtr;
+ * use rel_ptr::mmap::Mmap;
+ *
+ * // the file is not modified while it is mapped
+ * let map = unsafe { Mmap::open("image.bin")? };
+ * let root = map.root::<RelPtr<[u32], i32>>()?;
+ *
+ * println!("{:?}", unsafe { root.as_ref() });
+ * # Ok(())
+ * # }
+ * ```
+ *
+ * # Safety
+ *
+ * The file must not be modified while it is mapped, see `Mmap::open`
+ */
+pub struct Mmap {
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+unsafe impl Send for Mmap {}
+unsafe impl Sync for Mmap {}
+
+impl Mmap {
+    /**
+     * Map the file at `path` into memory
+     *
+     * # Safety
+     *
+     * The file must not be truncated or modified, by this or any other process, until the
+     * `Mmap` is dropped. Truncating it makes reads fault, and modifying it can make values
+     * that were already validated invalid
+     */
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<Self, MmapError> {
+        let file = File::open(path)?;
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "file is too large to map"))?;
+
+        if len < mem::size_of::<Header>() {
+            return Err(MmapError::TooSmall);
+        }
+
+        let ptr = unsafe {
+            libc::mmap(ptr::null_mut(), len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0)
+        };
+
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        match NonNull::new(ptr as *mut u8) {
+            Some(ptr) => Ok(Self { ptr, len }),
+            None => Err(io::Error::new(io::ErrorKind::Other, "mapped file to null").into()),
+        }
+    }
+
+    /// The contents of the file
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// The header of the image
+    pub fn header(&self) -> Result<Header, MmapError> {
+        Header::read(self.as_bytes())
+    }
+
+    /// Check the header, and validate everything reachable from the root,
+    /// then return the root, see `load_root`
+    pub fn root<T: Validate>(&self) -> Result<&T, MmapError> {
+        load_root::<T>(self.as_bytes())
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr.as_ptr() as *mut libc::c_void, self.len);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use super::{RelPtr, MetaData, Delta};
+
+use core::mem::MaybeUninit;
+
+impl<T: ?Sized + MetaData, I: Delta> RelPtr<T, I> {
+    /**
+     * Get a raw pointer to a field of the pointee, without creating
+     * a reference to the whole pointee
+     *
+     * `field` is given a raw pointer to the pointee, and must return a pointer
+     * into the same allocation, for example with `core::ptr::addr_of!`.
+     * The `project!` macro does this for you.
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_raw_unchecked`
+     */
+    #[inline]
+    pub unsafe fn project<F: ?Sized, P>(&self, field: P) -> *const F
+    where
+        P: FnOnce(*const T) -> *const F
+    {
+        field(self.as_raw_unchecked_impl())
+    }
+
+    /**
+     * Set `dest` to point to a field of the pointee, without creating
+     * a reference to the whole pointee, or a mutable reference to the field
+     *
+     * `field` is the same as in `RelPtr::project`. If the offset to the field
+     * cannot be stored in `J`, then `Err` will be returned, and there will be
+     * **no** change to `dest`
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_raw_unchecked`, and `field` must return a pointer to a
+     * valid value of type `F`
+     */
+    #[inline]
+    pub unsafe fn project_to<F: ?Sized + MetaData, J: Delta, P>(&self, dest: &mut RelPtr<F, J>, field: P) -> Result<(), J::Error>
+    where
+        P: FnOnce(*const T) -> *const F
+    {
+        let field = self.project(field);
+
+        // only a shared reference is made, for the metadata, so the field can still be aliased
+        dest.0 = J::sub(field as *const u8 as *mut u8, dest as *mut RelPtr<F, J> as *mut u8)?;
+        dest.1 = MaybeUninit::new(F::data(&*field));
+
+        Ok(())
+    }
+}
+
+/**
+ * Get a raw pointer to a field of the pointee of a `RelPtr`,
+ * without creating a reference to the whole pointee
+ *
+ * This must be called in an `unsafe` block, see `RelPtr::project`
+ *
+ * ```rust
+ * # fn main() {
+ * # use rel_ptr::{RelPtr, project};
+ * struct Outer {
+ *     inner: (u8, u16),
+ * }
+ *
+ * struct Holder {
+ *     value: Outer,
+ *     ptr: RelPtr<Outer, i8>,
+ * }
+ *
+ * let mut holder = Holder { value: Outer { inner: (1, 2) }, ptr: RelPtr::null() };
+ *
+ * holder.ptr.set(&mut holder.value).unwrap();
+ *
+ * let field: *const u16 = unsafe { project!(holder.ptr, inner.1) };
+ *
+ * assert_eq!(unsafe { *field }, 2);
+ * # }
+ * ```
+ */
+#[macro_export]
+macro_rules! project {
+    ($ptr:expr, $($field:tt).+) => {
+        $crate::RelPtr::project(&$ptr, |outer| ::core::ptr::addr_of!((*outer).$($field).+))
+    };
+}
@@ -0,0 +1,144 @@
+use super::{RelPtr, TaggedRelPtr, MetaData, IntegerDelta, IntegerDeltaError, RelTrace, RelVisitor, RelVisitorMut};
+
+/// The block of memory that was moved, see `rebase`
+struct Block {
+    old: usize,
+    new: usize,
+    len: usize,
+}
+
+impl Block {
+    /**
+     * The new offset for the relative pointer at `addr`, or `None` if it doesn't need to change
+     *
+     * Relative pointers that point inside of the block moved together with their pointees,
+     * so they are still correct. Relative pointers outside of the block were not moved.
+     */
+    fn new_offset<I: IntegerDelta>(&self, addr: usize, offset: isize) -> Option<Result<I, IntegerDeltaError>> {
+        let pos = addr.wrapping_sub(self.new);
+
+        if pos >= self.len {
+            return None;
+        }
+
+        let old_target = self.old.wrapping_add(pos).wrapping_add(offset as usize);
+
+        if old_target.wrapping_sub(self.old) < self.len {
+            None
+        } else {
+            Some(I::sub(old_target as *mut u8, addr as *mut u8))
+        }
+    }
+
+    /// The new offset for a tagged relative pointer, with its tag, see `Block::new_offset`
+    fn new_tagged_offset<T: ?Sized + MetaData, I: IntegerDelta, const BITS: u32>(
+        &self,
+        ptr: &TaggedRelPtr<T, I, BITS>,
+    ) -> Option<Result<I, IntegerDeltaError>> {
+        let offset = self.new_offset::<I>(ptr as *const _ as usize, ptr.offset())?;
+
+        Some(offset.and_then(|offset| TaggedRelPtr::<T, I, BITS>::tagged_offset(offset.to_isize(), ptr.tag())))
+    }
+}
+
+/// Checks that all relative pointers can be rebased
+struct Check<'a>(&'a Block, Result<(), IntegerDeltaError>);
+
+impl RelVisitor for Check<'_> {
+    fn visit<T: ?Sized + MetaData, I: IntegerDelta>(&mut self, ptr: &RelPtr<T, I>) {
+        if self.1.is_ok() {
+            if let Some(Err(err)) = self.0.new_offset::<I>(ptr as *const _ as usize, ptr.0.to_isize()) {
+                self.1 = Err(err);
+            }
+        }
+    }
+
+    fn visit_tagged<T: ?Sized + MetaData, I: IntegerDelta, const BITS: u32>(&mut self, ptr: &TaggedRelPtr<T, I, BITS>) {
+        if self.1.is_ok() {
+            if let Some(Err(err)) = self.0.new_tagged_offset(ptr) {
+                self.1 = Err(err);
+            }
+        }
+    }
+}
+
+/// Rebases all relative pointers, after they were checked
+struct Apply<'a>(&'a Block);
+
+impl RelVisitorMut for Apply<'_> {
+    fn visit_mut<T: ?Sized + MetaData, I: IntegerDelta>(&mut self, ptr: &mut RelPtr<T, I>) {
+        if let Some(Ok(offset)) = self.0.new_offset(ptr as *const _ as usize, ptr.0.to_isize()) {
+            ptr.0 = offset;
+        }
+    }
+
+    fn visit_tagged_mut<T: ?Sized + MetaData, I: IntegerDelta, const BITS: u32>(&mut self, ptr: &mut TaggedRelPtr<T, I, BITS>) {
+        if let Some(Ok(offset)) = self.0.new_tagged_offset(ptr) {
+            ptr.0 .0 = offset;
+        }
+    }
+}
+
+/**
+ * Fix up the relative pointers in `value` after the block of memory that
+ * contains it was moved from `old` to `new` (without moving the pointees
+ * outside of the block)
+ *
+ * `len` is the size of the block. Relative pointers that point inside of the block
+ * moved together with their pointees, so they are left alone. Relative pointers
+ * that point outside of the block are rewritten so that they still point to the
+ * same pointee. Relative pointers in `value` that are outside of the new block are
+ * left alone.
+ *
+ * If any offset can't be stored in its `Delta`, then `Err` is returned, and
+ * **no** relative pointer is changed
+ *
+ * ```rust
+ * # fn main() {
+ * use rel_ptr::{RelPtr, impl_rel_trace, rebase_moved};
+ *
+ * struct Node {
+ *     value: u32,
+ *     this: RelPtr<u32>,
+ *     other: RelPtr<u32>,
+ * }
+ *
+ * impl_rel_trace!(Node { this, other });
+ *
+ * let mut outside = 20;
+ * let mut node = Node { value: 10, this: RelPtr::null(), other: RelPtr::null() };
+ *
+ * node.this.set(&mut node.value).unwrap();
+ * node.other.set(&mut outside).unwrap();
+ *
+ * let old = &node as *const Node;
+ * let mut node = Box::new(node);
+ *
+ * rebase_moved(&mut *node, old).unwrap();
+ *
+ * assert_eq!(unsafe { *node.this.as_ref_unchecked() }, 10);
+ * assert_eq!(unsafe { *node.other.as_ref_unchecked() }, 20);
+ * # }
+ * ```
+ */
+pub fn rebase<T: ?Sized + RelTrace>(value: &mut T, old: *const u8, new: *const u8, len: usize) -> Result<(), IntegerDeltaError> {
+    let block = Block { old: old as usize, new: new as usize, len };
+    let mut check = Check(&block, Ok(()));
+
+    value.trace(&mut check);
+    check.1?;
+
+    value.trace_mut(&mut Apply(&block));
+
+    Ok(())
+}
+
+/**
+ * Fix up the relative pointers in `value` after it was moved from `old`,
+ * see `rebase`
+ */
+pub fn rebase_moved<T: RelTrace>(value: &mut T, old: *const T) -> Result<(), IntegerDeltaError> {
+    let new = value as *const T as *const u8;
+
+    rebase(value, old as *const u8, new, std::mem::size_of::<T>())
+}
@@ -0,0 +1,148 @@
+/*!
+ * [serde](https://serde.rs) integration, enabled with the `serde` feature
+ *
+ * A `RelPtr` can't be serialized on its own, because its offset is only meaningful
+ * relative to where it is in memory. Instead, serialize the data that the relative
+ * pointers point into, and call `RelPtr::set` again after deserializing. The
+ * `SelfReferential` trait describes how to do this, and `self_ref` and `Persist`
+ * use it to implement serialization.
+ *
+ * ```rust
+ * # fn main() {
+ * use rel_ptr::RelPtr;
+ * use rel_ptr::serde::{SelfReferential, Persist};
+ *
+ * struct SelfRef {
+ *     value: (String, u32),
+ *     ptr: RelPtr<String, i8>,
+ * }
+ *
+ * impl SelfReferential for SelfRef {
+ *     type Data = (String, u32);
+ *     type Error = rel_ptr::IntegerDeltaError;
+ *
+ *     fn data(&self) -> &Self::Data {
+ *         &self.value
+ *     }
+ *
+ *     fn from_data(value: Self::Data) -> Result<Self, Self::Error> {
+ *         let mut this = Self { value, ptr: RelPtr::null() };
+ *
+ *         this.ptr.set(&mut this.value.0)?;
+ *
+ *         Ok(this)
+ *     }
+ * }
+ *
+ * let s = SelfRef::from_data(("Hello World".into(), 10)).unwrap();
+ * let json = serde_json::to_string(&Persist(s)).unwrap();
+ *
+ * assert_eq!(json, r#"["Hello World",10]"#);
+ *
+ * let Persist(s): Persist<SelfRef> = serde_json::from_str(&json).unwrap();
+ *
+ * assert_eq!(unsafe { s.ptr.as_ref_unchecked() }, "Hello World");
+ * # }
+ * ```
+ */
+
+use super::{RelPtr, MetaData, Nullable};
+
+use ::serde::{Serialize, Serializer, Deserialize, Deserializer};
+use ::serde::de::Error as _;
+
+use std::fmt::Display;
+
+/**
+ * A self-referential type, whose relative pointers can be
+ * recomputed from the data that it owns
+ */
+pub trait SelfReferential: Sized {
+    /// The owned data, this is what gets serialized
+    type Data;
+
+    /// The error if the relative pointers could not be set
+    type Error: Display;
+
+    /// Get the owned data
+    fn data(&self) -> &Self::Data;
+
+    /// Rebuild the type from the owned data, this should set all relative pointers
+    fn from_data(data: Self::Data) -> Result<Self, Self::Error>;
+}
+
+/**
+ * Serialize and deserialize a `SelfReferential` field, use with
+ * `#[serde(with = "rel_ptr::serde::self_ref")]`
+ */
+pub mod self_ref {
+    use super::*;
+
+    /// Serialize the owned data of `value`
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: SelfReferential,
+        T::Data: Serialize,
+        S: Serializer,
+    {
+        value.data().serialize(serializer)
+    }
+
+    /// Deserialize the owned data, and rebuild the value from it
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: SelfReferential,
+        T::Data: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::from_data(T::Data::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+/// A wrapper that implements `Serialize` and `Deserialize` for `SelfReferential` types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Persist<T>(pub T);
+
+impl<T: SelfReferential> Serialize for Persist<T>
+where
+    T::Data: Serialize
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self_ref::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, T: SelfReferential> Deserialize<'de> for Persist<T>
+where
+    T::Data: Deserialize<'de>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        self_ref::deserialize(deserializer).map(Persist)
+    }
+}
+
+/**
+ * Serializes the value that a relative pointer points to,
+ * or none if the relative pointer is null
+ */
+pub struct SerializeTarget<'a, T: ?Sized + MetaData, I: Nullable>(&'a RelPtr<T, I>);
+
+impl<'a, T: ?Sized + MetaData, I: Nullable> SerializeTarget<'a, T, I> {
+    /**
+     * Create a new `SerializeTarget`
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_ref`, for as long as the `SerializeTarget` is used
+     */
+    pub unsafe fn new(ptr: &'a RelPtr<T, I>) -> Self {
+        Self(ptr)
+    }
+}
+
+impl<T: ?Sized + MetaData + Serialize, I: Nullable> Serialize for SerializeTarget<'_, T, I> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // safety is guaranteed by `SerializeTarget::new`
+        unsafe { self.0.as_ref() }.serialize(serializer)
+    }
+}
@@ -0,0 +1,383 @@
+/*!
+ * Shared memory regions that can be used by multiple processes, enabled with the `shm` feature
+ *
+ * This is only available on Linux, and requires `std`
+ *
+ * Each process can map the same shared memory at a different address, so normal pointers
+ * can't be stored in it. But relative pointers only depend on the distance between
+ * the pointer and its pointee, so structures that are linked with `RelPtr` can be traversed
+ * by any process that maps the region. `SharedRegion` creates or opens the memory, and
+ * `SharedHeap` places a root object in it, and allocates other values next to it.
+ */
+
+use std::ffi::CString;
+use std::marker::PhantomData;
+use std::os::raw::c_int;
+use std::os::unix::io::RawFd;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{io, mem, ptr};
+
+/// The magic bytes at the start of every `SharedHeap`
+const MAGIC: [u8; 8] = *b"RELSHM\0\0";
+
+/// Convert the result of a libc call into an `io::Result`
+fn cvt(res: c_int) -> io::Result<c_int> {
+    if res == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(res)
+    }
+}
+
+/**
+ * A shared memory region mapped into this process
+ *
+ * The region is unmapped and its file descriptor is closed when it is dropped
+ */
+#[derive(Debug)]
+pub struct SharedRegion {
+    ptr: NonNull<u8>,
+    len: usize,
+    fd: RawFd,
+}
+
+unsafe impl Send for SharedRegion {}
+unsafe impl Sync for SharedRegion {}
+
+impl SharedRegion {
+    /// Create a new anonymous region of `len` bytes, backed by a `memfd`,
+    /// which can be shared with child processes, or by passing its file descriptor
+    pub fn create(len: usize) -> io::Result<Self> {
+        let name = CString::new("rel-ptr").unwrap();
+        let fd = cvt(unsafe { libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC) })?;
+
+        unsafe { Self::init(fd, len) }
+    }
+
+    /**
+     * Open the POSIX shared memory object `name`, creating it if it doesn't exist,
+     * and map `len` bytes of it. `name` should start with a `/`
+     *
+     * Only a newly created object is resized to `len` bytes. If the object already
+     * exists, then it is left as it is, so that the regions that other processes mapped
+     * stay valid, and `Err` is returned if it is smaller than `len` bytes
+     *
+     * The object is not removed when the region is dropped, see `SharedRegion::unlink`
+     */
+    pub fn open(name: &str, len: usize) -> io::Result<Self> {
+        let name = CString::new(name).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        let flags = libc::O_RDWR | libc::O_CLOEXEC;
+
+        match cvt(unsafe { libc::shm_open(name.as_ptr(), flags | libc::O_CREAT | libc::O_EXCL, 0o600) }) {
+            Ok(fd) => return unsafe { Self::init(fd, len) },
+            Err(err) if err.raw_os_error() != Some(libc::EEXIST) => return Err(err),
+            Err(_) => (),
+        }
+
+        let fd = cvt(unsafe { libc::shm_open(name.as_ptr(), flags, 0) })?;
+
+        unsafe {
+            let mut stat = mem::zeroed::<libc::stat>();
+
+            let res = match cvt(libc::fstat(fd, &mut stat)) {
+                Ok(_) if (stat.st_size as u64) < len as u64 => Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("the shared memory object is {} bytes, but {} bytes were requested", stat.st_size, len),
+                )),
+                res => res,
+            };
+
+            if let Err(err) = res {
+                libc::close(fd);
+                return Err(err);
+            }
+
+            Self::map(fd, len)
+        }
+    }
+
+    /// Remove the POSIX shared memory object `name`, regions
+    /// that are already mapped stay valid
+    pub fn unlink(name: &str) -> io::Result<()> {
+        let name = CString::new(name).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+
+        cvt(unsafe { libc::shm_unlink(name.as_ptr()) }).map(drop)
+    }
+
+    /**
+     * Map a region from a file descriptor, for example one that was
+     * received from another process. The region takes ownership of `fd`
+     *
+     * # Safety
+     *
+     * `fd` must be a valid file descriptor for a shared memory object that
+     * is at least `len` bytes long, and must not be used after this call
+     */
+    pub unsafe fn from_fd(fd: RawFd, len: usize) -> io::Result<Self> {
+        Self::map(fd, len)
+    }
+
+    /// Set the size of a new `fd` to `len`, and map it
+    unsafe fn init(fd: RawFd, len: usize) -> io::Result<Self> {
+        if let Err(err) = cvt(libc::ftruncate(fd, len as libc::off_t)) {
+            libc::close(fd);
+            return Err(err);
+        }
+
+        Self::map(fd, len)
+    }
+
+    /// Map `len` bytes of `fd`
+    unsafe fn map(fd: RawFd, len: usize) -> io::Result<Self> {
+        let ptr = libc::mmap(ptr::null_mut(), len, libc::PROT_READ | libc::PROT_WRITE, libc::MAP_SHARED, fd, 0);
+
+        match NonNull::new(ptr as *mut u8) {
+            Some(ptr) if ptr.as_ptr() as *mut libc::c_void != libc::MAP_FAILED => Ok(Self { ptr, len, fd }),
+            _ => {
+                let err = io::Error::last_os_error();
+                libc::close(fd);
+                Err(err)
+            }
+        }
+    }
+
+    /// Map the same memory again, at a different address
+    pub fn remap(&self) -> io::Result<Self> {
+        let fd = cvt(unsafe { libc::fcntl(self.fd, libc::F_DUPFD_CLOEXEC, 0) })?;
+
+        unsafe { Self::map(fd, self.len) }
+    }
+
+    /// The file descriptor of the region
+    pub fn fd(&self) -> RawFd {
+        self.fd
+    }
+
+    /// The size of the region in bytes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the region has a size of zero
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// A pointer to the start of the region
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+}
+
+impl Drop for SharedRegion {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr.as_ptr() as *mut libc::c_void, self.len);
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// The start of a `SharedHeap`
+#[repr(C)]
+struct HeapHeader {
+    magic: [u8; 8],
+    len: usize,
+    next: AtomicUsize,
+    root: usize,
+}
+
+/**
+ * A root object of type `R` inside of a `SharedRegion`, and a bump allocator
+ * for the rest of the region
+ *
+ * Values are linked together with `RelPtr`s, so that any process that maps the
+ * region can follow them, no matter where the region is mapped. Allocation is
+ * lock-free and can be used from multiple processes at the same time, but
+ * synchronizing access to the values themselves is up to you.
+ *
+ * Only `Copy` types can be stored in the heap, because they can't own memory
+ * outside of the region, and they are never dropped
+ *
+ * ```rust
+ * # fn main() -> std::io::Result<()> {
+ * use rel_ptr::RelPtr;
+ * use rel_ptr::shm::SharedHeap;
+ *
+ * #[derive(Clone, Copy)]
+ * struct Root {
+ *     value: RelPtr<u64, i32>,
+ * }
+ *
+ * let mut heap = SharedHeap::create(4096, Root { value: RelPtr::null() })?;
+ * let (root, alloc) = heap.split();
+ *
+ * root.value.set(alloc.alloc(42).unwrap()).unwrap();
+ *
+ * // map the same memory at another address, like another process would
+ * let other = unsafe { SharedHeap::<Root>::open(heap.region().remap()?)? };
+ *
+ * assert_eq!(unsafe { *other.root().value.as_ref_unchecked() }, 42);
+ * # Ok(())
+ * # }
+ * ```
+ */
+pub struct SharedHeap<R> {
+    region: SharedRegion,
+    root: PhantomData<R>,
+}
+
+impl<R: Copy> SharedHeap<R> {
+    /// Create a new anonymous region of `len` bytes, and place `root` in it
+    pub fn create(len: usize, root: R) -> io::Result<Self> {
+        // the region was just created, so nothing else has mapped it yet
+        unsafe { Self::new(SharedRegion::create(len)?, root) }
+    }
+
+    /**
+     * Place `root` at the start of `region`, any data that was already
+     * in the region is ignored
+     *
+     * # Safety
+     *
+     * The same memory can be mapped more than once, with `SharedRegion::open`,
+     * `SharedRegion::remap` or by another process, and the heap gives out references
+     * into it. So the same rules as `SharedHeap::open` apply: other mappings of the region
+     * must not be used in ways that would be a data race with this heap, or that would
+     * alias the mutable references it gives out
+     */
+    pub unsafe fn new(region: SharedRegion, root: R) -> io::Result<Self> {
+        if region.len() < mem::size_of::<HeapHeader>() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "shared region is too small"));
+        }
+
+        unsafe {
+            ptr::write(region.as_ptr() as *mut HeapHeader, HeapHeader {
+                magic: MAGIC,
+                len: region.len(),
+                next: AtomicUsize::new(mem::size_of::<HeapHeader>()),
+                root: 0,
+            });
+        }
+
+        let heap = Self { region, root: PhantomData };
+        let root = heap.alloc(root)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "shared region is too small"))?
+            as *mut R as usize - heap.region.as_ptr() as usize;
+
+        unsafe {
+            (*(heap.region.as_ptr() as *mut HeapHeader)).root = root;
+        }
+
+        Ok(heap)
+    }
+
+    /**
+     * Open a heap that was created with `SharedHeap::create` or `SharedHeap::new`,
+     * possibly by another process
+     *
+     * # Safety
+     *
+     * The heap must have been created with the same root type `R`, and must not be accessed
+     * by other processes in ways that would be a data race with this one
+     */
+    pub unsafe fn open(region: SharedRegion) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "shared region does not contain a heap");
+
+        if region.len() < mem::size_of::<HeapHeader>() {
+            return Err(invalid());
+        }
+
+        let header = &*(region.as_ptr() as *const HeapHeader);
+
+        if header.magic != MAGIC || header.len != region.len() || header.root == 0 {
+            return Err(invalid());
+        }
+
+        Ok(Self { region, root: PhantomData })
+    }
+
+    /// The header at the start of the region
+    fn header(&self) -> &HeapHeader {
+        unsafe { &*(self.region.as_ptr() as *const HeapHeader) }
+    }
+
+    /// The region that holds the heap
+    pub fn region(&self) -> &SharedRegion {
+        &self.region
+    }
+
+    /// The number of bytes of the region that are in use
+    pub fn used(&self) -> usize {
+        self.header().next.load(Ordering::Acquire)
+    }
+
+    /// The root object
+    pub fn root(&self) -> &R {
+        unsafe { &*(self.region.as_ptr().add(self.header().root) as *const R) }
+    }
+
+    /// The root object
+    pub fn root_mut(&mut self) -> &mut R {
+        unsafe { &mut *(self.region.as_ptr().add(self.header().root) as *mut R) }
+    }
+
+    /// Get mutable access to the root object, and an allocator at the same time,
+    /// so that newly allocated values can be linked to the root
+    pub fn split(&mut self) -> (&mut R, Alloc<'_>) {
+        let alloc = Alloc { region: &self.region };
+        let root = unsafe { &mut *(self.region.as_ptr().add(self.header().root) as *mut R) };
+
+        (root, alloc)
+    }
+
+    /// An allocator for the heap
+    pub fn allocator(&self) -> Alloc<'_> {
+        Alloc { region: &self.region }
+    }
+
+    /// Move `value` into the region, see `Alloc::alloc`
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T: Copy>(&self, value: T) -> Option<&mut T> {
+        self.allocator().alloc(value)
+    }
+}
+
+/// Allocates values inside of a `SharedHeap`, see `SharedHeap::split`
+#[derive(Clone, Copy)]
+pub struct Alloc<'a> {
+    region: &'a SharedRegion,
+}
+
+impl<'a> Alloc<'a> {
+    /**
+     * Move `value` into the region, or return `None` if there is not enough space left
+     *
+     * The returned reference can be passed to `RelPtr::set` to link it to other values in the region
+     */
+    pub fn alloc<T: Copy>(&self, value: T) -> Option<&'a mut T> {
+        let next = unsafe { &(*(self.region.as_ptr() as *const HeapHeader)).next };
+        let mut pos = next.load(Ordering::Relaxed);
+
+        loop {
+            let align = mem::align_of::<T>();
+            let start = pos.checked_add(align - 1)? & !(align - 1);
+            let end = start.checked_add(mem::size_of::<T>())?;
+
+            if end > self.region.len() {
+                return None;
+            }
+
+            match next.compare_exchange_weak(pos, end, Ordering::AcqRel, Ordering::Relaxed) {
+                Ok(_) => unsafe {
+                    // the memory in `start..end` was just reserved for this value
+                    let slot = self.region.as_ptr().add(start) as *mut T;
+                    ptr::write(slot, value);
+                    return Some(&mut *slot);
+                },
+                Err(current) => pos = current,
+            }
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use super::{RelPtr, Nullable};
+
+use std::ops::{Bound, RangeBounds};
+use std::ptr::NonNull;
+
+/// Convert a range into `start..end`, if it is in bounds of `len`
+fn bounds<R: RangeBounds<usize>>(range: R, len: usize) -> Option<(usize, usize)> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.checked_add(1)?,
+        Bound::Unbounded => 0,
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.checked_add(1)?,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    if start <= end && end <= len {
+        Some((start, end))
+    } else {
+        None
+    }
+}
+
+impl<T, I: Nullable> RelPtr<[T], I> {
+    /**
+     * The pointer to the first element, and the number of elements
+     *
+     * A null relative pointer is treated as an empty slice
+     *
+     * # Safety
+     *
+     * The relative pointer must have been successfully set, or be null
+     */
+    #[inline]
+    unsafe fn raw_parts(&self) -> (*const T, usize) {
+        if self.is_null() {
+            (NonNull::dangling().as_ptr(), 0)
+        } else {
+            (self.as_raw_unchecked_impl() as *const T, self.1.assume_init())
+        }
+    }
+
+    /**
+     * The length of the slice, this only reads the length stored
+     * inside of the relative pointer, and does not touch the pointee
+     *
+     * A null relative pointer has a length of 0
+     *
+     * # Safety
+     *
+     * The relative pointer must have been successfully set, or be null
+     */
+    #[inline]
+    pub unsafe fn len(&self) -> usize {
+        self.raw_parts().1
+    }
+
+    /**
+     * Check if the slice is empty, see `RelPtr::len`
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::len`
+     */
+    #[inline]
+    pub unsafe fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /**
+     * Get a reference to the element at `idx`, or `None` if it is out of bounds
+     *
+     * The bounds check only uses the stored length, and only the element at `idx` is
+     * ever referenced
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_ref`
+     */
+    #[inline]
+    pub unsafe fn get(&self, idx: usize) -> Option<&T> {
+        let (ptr, len) = self.raw_parts();
+
+        if idx < len {
+            Some(&*ptr.add(idx))
+        } else {
+            None
+        }
+    }
+
+    /**
+     * Get a mutable reference to the element at `idx`, or `None` if it is out of bounds
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_mut`
+     */
+    #[inline]
+    pub unsafe fn get_mut(&mut self, idx: usize) -> Option<&mut T> {
+        let (ptr, len) = self.raw_parts();
+
+        if idx < len {
+            Some(&mut *(ptr as *mut T).add(idx))
+        } else {
+            None
+        }
+    }
+
+    /**
+     * Get a reference to a sub-range of the slice, or `None` if it is out of bounds
+     *
+     * The bounds check only uses the stored length, and only the elements in `range`
+     * are ever referenced
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_ref`
+     */
+    #[inline]
+    pub unsafe fn subslice<R: RangeBounds<usize>>(&self, range: R) -> Option<&[T]> {
+        let (ptr, len) = self.raw_parts();
+        let (start, end) = bounds(range, len)?;
+
+        Some(std::slice::from_raw_parts(ptr.add(start), end - start))
+    }
+
+    /**
+     * Divide the slice into two at `mid`, or `None` if `mid > len`
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_ref`
+     */
+    #[inline]
+    pub unsafe fn split_at(&self, mid: usize) -> Option<(&[T], &[T])> {
+        let (ptr, len) = self.raw_parts();
+
+        if mid <= len {
+            Some((
+                std::slice::from_raw_parts(ptr, mid),
+                std::slice::from_raw_parts(ptr.add(mid), len - mid),
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+impl<I: Nullable> RelPtr<str, I> {
+    /**
+     * Get the underlying bytes of the string
+     *
+     * A null relative pointer is treated as an empty string
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_ref`
+     */
+    #[inline]
+    pub unsafe fn as_bytes(&self) -> &[u8] {
+        if self.is_null() {
+            &[]
+        } else {
+            std::slice::from_raw_parts(self.as_raw_unchecked_impl() as *const u8, self.1.assume_init())
+        }
+    }
+
+    /**
+     * The length of the string in bytes, this only reads the length stored
+     * inside of the relative pointer, and does not touch the pointee
+     *
+     * A null relative pointer has a length of 0
+     *
+     * # Safety
+     *
+     * The relative pointer must have been successfully set, or be null
+     */
+    #[inline]
+    pub unsafe fn len(&self) -> usize {
+        if self.is_null() {
+            0
+        } else {
+            self.1.assume_init()
+        }
+    }
+
+    /**
+     * Check if the string is empty, see `RelPtr::len`
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::len`
+     */
+    #[inline]
+    pub unsafe fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /**
+     * Get a sub-range of the string, or `None` if it is out of bounds,
+     * or not on a `char` boundary
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_ref`
+     */
+    #[inline]
+    pub unsafe fn subslice<R: RangeBounds<usize>>(&self, range: R) -> Option<&str> {
+        let (start, end) = bounds(range, self.len())?;
+
+        self.as_str().get(start..end)
+    }
+
+    /**
+     * An iterator over the bytes of the string
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_ref`
+     */
+    #[inline]
+    pub unsafe fn bytes(&self) -> std::str::Bytes<'_> {
+        self.as_str().bytes()
+    }
+
+    /**
+     * An iterator over the `char`s of the string
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_ref`
+     */
+    #[inline]
+    pub unsafe fn chars(&self) -> std::str::Chars<'_> {
+        self.as_str().chars()
+    }
+
+    /// The string, or `""` if the relative pointer is null
+    #[inline]
+    unsafe fn as_str(&self) -> &str {
+        std::str::from_utf8_unchecked(self.as_bytes())
+    }
+}
+
+impl<T, I: Nullable> RelPtr<T, I> {
+    /**
+     * Find the index of the pointee in `base`, or `None` if the relative
+     * pointer is null or does not point to an element of `base`
+     *
+     * This is useful to serialize a relative pointer as an index,
+     * and `RelPtr::set` it to `&mut base[index]` again afterwards
+     *
+     * # Safety
+     *
+     * The relative pointer must have been successfully set, or be null
+     */
+    pub unsafe fn index_in(&self, base: &[T]) -> Option<usize> {
+        if self.is_null() || std::mem::size_of::<T>() == 0 {
+            return None;
+        }
+
+        let target = self.as_raw_unchecked_impl() as usize;
+        let start = base.as_ptr() as usize;
+        let offset = target.checked_sub(start)?;
+
+        if offset % std::mem::size_of::<T>() == 0 && offset / std::mem::size_of::<T>() < base.len() {
+            Some(offset / std::mem::size_of::<T>())
+        } else {
+            None
+        }
+    }
+}
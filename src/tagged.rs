@@ -0,0 +1,226 @@
+use super::{RelPtr, MetaData, Nullable, IntegerDelta, IntegerDeltaError, IntegerDeltaErrorImpl, nn_to_ptr};
+use super::{RelTrace, RelVisitor, RelVisitorMut};
+
+use core::mem::MaybeUninit;
+use std::ptr::NonNull;
+
+/**
+ * A relative pointer that stores a small tag in the low bits of its offset
+ *
+ * If both the `TaggedRelPtr` and its pointee are aligned to at least `2^BITS` bytes,
+ * then the lowest `BITS` bits of the offset are always zero, so they can be used to
+ * store extra data, like the colour of a node in a red-black tree. The tag is masked
+ * off whenever the pointer is resolved.
+ *
+ * ```rust
+ * # fn main() {
+ * # use rel_ptr::TaggedRelPtr;
+ * struct Node {
+ *     value: u32,
+ *     ptr: TaggedRelPtr<u32, i32, 2>,
+ * }
+ *
+ * let mut node = Node { value: 10, ptr: TaggedRelPtr::null() };
+ *
+ * node.ptr.set(&mut node.value).unwrap();
+ * node.ptr.set_tag(3);
+ *
+ * assert_eq!(node.ptr.tag(), 3);
+ * assert_eq!(unsafe { *node.ptr.as_ref_unchecked() }, 10);
+ * # }
+ * ```
+ *
+ * # Safety
+ *
+ * The same rules as `RelPtr` apply
+ */
+#[repr(transparent)]
+pub struct TaggedRelPtr<T: ?Sized + MetaData, I: IntegerDelta, const BITS: u32>(pub(crate) RelPtr<T, I>);
+
+impl<T: ?Sized + MetaData, I: IntegerDelta, const BITS: u32> Copy for TaggedRelPtr<T, I, BITS> {}
+impl<T: ?Sized + MetaData, I: IntegerDelta, const BITS: u32> Clone for TaggedRelPtr<T, I, BITS> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized + MetaData, I: IntegerDelta + Nullable, const BITS: u32> TaggedRelPtr<T, I, BITS> {
+    /// A null tagged relative pointer has an offset of 0 (points to itself) and a tag of 0
+    #[inline(always)]
+    pub fn null() -> Self {
+        Self(RelPtr::null())
+    }
+
+    /// Check if the tagged relative pointer is null, ignoring the tag
+    #[inline(always)]
+    pub fn is_null(&self) -> bool {
+        self.offset() == 0
+    }
+
+    /**
+     * Gets a reference from the tagged relative pointer,
+     * if the relative pointer is null, then `None` is
+     * returned
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_ref`
+     */
+    #[inline]
+    pub unsafe fn as_ref(&self) -> Option<&T> {
+        if self.is_null() {
+            None
+        } else {
+            Some(&*self.as_raw_unchecked_impl())
+        }
+    }
+
+    /**
+     * Gets a mutable reference from the tagged relative pointer,
+     * if the relative pointer is null, then `None` is
+     * returned
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_mut`
+     */
+    #[inline]
+    pub unsafe fn as_mut(&mut self) -> Option<&mut T> {
+        if self.is_null() {
+            None
+        } else {
+            Some(&mut *self.as_raw_unchecked())
+        }
+    }
+}
+
+impl<T: ?Sized + MetaData, I: IntegerDelta, const BITS: u32> TaggedRelPtr<T, I, BITS> {
+    /// The mask of the bits in the offset that are used by the tag,
+    /// this is also the largest tag that can be stored
+    pub const TAG_MASK: usize = (1 << BITS) - 1;
+
+    /// The offset with the tag masked off
+    #[inline(always)]
+    pub fn offset(&self) -> isize {
+        self.0 .0.to_isize() & !(Self::TAG_MASK as isize)
+    }
+
+    /// The offset `del` with `tag` in its low bits, or `Err` if they aren't clear
+    pub(crate) fn tagged_offset(del: isize, tag: usize) -> Result<I, IntegerDeltaError> {
+        if del as usize & Self::TAG_MASK != 0 {
+            return Err(IntegerDeltaError(IntegerDeltaErrorImpl::Misaligned(del)));
+        }
+
+        I::from_isize(del | tag as isize)
+    }
+
+    /// Get the tag stored in the low bits of the offset
+    #[inline(always)]
+    pub fn tag(&self) -> usize {
+        self.0 .0.to_isize() as usize & Self::TAG_MASK
+    }
+
+    /**
+     * Set the tag stored in the low bits of the offset,
+     * this does not change where the pointer points to
+     *
+     * # Panics
+     *
+     * If `tag > Self::TAG_MASK`
+     */
+    #[inline]
+    pub fn set_tag(&mut self, tag: usize) {
+        assert!(tag <= Self::TAG_MASK, "tag {} does not fit in {} bits", tag, BITS);
+
+        self.0 .0 = I::from_isize(self.offset() | tag as isize)
+            .expect("Tried to tag an offset that is not valid for the given `Delta`");
+    }
+
+    /**
+     * Set the offset of the tagged relative pointer, keeping the current tag
+     *
+     * If `align_of::<T>()` is less than `2^BITS`, or if the offset does not have its
+     * lowest `BITS` bits clear (because the `TaggedRelPtr` is not aligned enough),
+     * then `Err` will be returned. Like `RelPtr::set`, there will be **no** change to
+     * the pointer on error.
+     */
+    #[inline]
+    pub fn set(&mut self, value: &mut T) -> Result<(), IntegerDeltaError> {
+        let tag = self.tag();
+        let del = I::sub(value as *mut T as _, self as *mut Self as _)?.to_isize();
+
+        if std::mem::align_of_val(value) <= Self::TAG_MASK {
+            return Err(IntegerDeltaError(IntegerDeltaErrorImpl::Misaligned(del)));
+        }
+
+        self.0 .0 = Self::tagged_offset(del, tag)?;
+        self.0 .1 = MaybeUninit::new(T::data(value));
+
+        Ok(())
+    }
+
+    /**
+     * Converts the tagged relative pointer into a normal raw pointer
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_raw_unchecked`
+     */
+    #[inline]
+    pub(crate) unsafe fn as_raw_unchecked_impl(&self) -> *const T {
+        let ptr = (self as *const Self as *const u8).offset(self.offset());
+
+        nn_to_ptr(T::compose(
+            NonNull::new(ptr as *mut u8),
+            self.0 .1.assume_init()
+        ))
+    }
+
+    /**
+     * Converts the tagged relative pointer into a normal raw pointer
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_raw_unchecked`
+     */
+    #[inline]
+    pub unsafe fn as_raw_unchecked(&mut self) -> *mut T {
+        self.as_raw_unchecked_impl() as _
+    }
+
+    /**
+     * Gets a reference from the tagged relative pointer
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_raw_unchecked`
+     */
+    #[inline]
+    pub unsafe fn as_ref_unchecked(&self) -> &T {
+        &*self.as_raw_unchecked_impl()
+    }
+
+    /**
+     * Gets a mutable reference from the tagged relative pointer
+     *
+     * # Safety
+     *
+     * Same as `RelPtr::as_raw_unchecked`
+     */
+    #[inline]
+    pub unsafe fn as_mut_unchecked(&mut self) -> &mut T {
+        &mut *self.as_raw_unchecked()
+    }
+}
+
+impl<T: ?Sized + MetaData, I: IntegerDelta, const BITS: u32> RelTrace for TaggedRelPtr<T, I, BITS> {
+    #[inline]
+    fn trace<V: RelVisitor + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit_tagged(self)
+    }
+
+    #[inline]
+    fn trace_mut<V: RelVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_tagged_mut(self)
+    }
+}
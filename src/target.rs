@@ -0,0 +1,166 @@
+use super::{RelPtr, MetaData, IntegerDelta};
+
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/**
+ * The resolved target of a relative pointer, its address and metadata
+ *
+ * Unlike `RelPtr`, a `TargetKey` doesn't depend on where it is stored, so it
+ * can be used as a key in sets and maps, for example to find all of the relative
+ * pointers to a node while building a graph. It is ordered by address and then
+ * by metadata, and all null relative pointers have the same key.
+ *
+ * ```rust
+ * # fn main() {
+ * use rel_ptr::{RelPtr, TargetKey};
+ *
+ * let mut value = (0_u8, [RelPtr::<u8, i8>::null(); 2]);
+ *
+ * value.1[0].set(&mut value.0).unwrap();
+ * value.1[1].set(&mut value.0).unwrap();
+ *
+ * // the relative pointers have different offsets, but the same target
+ * assert_ne!(value.1[0].offset(), value.1[1].offset());
+ * assert!(unsafe { value.1[0].ptr_eq_target(&value.1[1]) });
+ * assert!(unsafe { value.1[0].target_key() == TargetKey::of(&value.0) });
+ * # }
+ * ```
+ */
+pub struct TargetKey<T: ?Sized + MetaData> {
+    addr: *const u8,
+    data: Option<T::Data>,
+    mark: PhantomData<*const T>,
+}
+
+impl<T: ?Sized + MetaData> TargetKey<T> {
+    /// The key of relative pointers that point to `value`
+    #[inline]
+    pub fn of(value: &T) -> Self {
+        Self {
+            addr: value as *const T as *const u8,
+            data: Some(T::data(value)),
+            mark: PhantomData,
+        }
+    }
+
+    /// The key of null relative pointers
+    #[inline]
+    pub fn null() -> Self {
+        Self {
+            addr: std::ptr::null(),
+            data: None,
+            mark: PhantomData,
+        }
+    }
+
+    /// Check if this is the key of null relative pointers
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        self.data.is_none()
+    }
+
+    /// The address of the target, this is null for null relative pointers
+    #[inline]
+    pub fn addr(&self) -> *const u8 {
+        self.addr
+    }
+
+    /// The metadata of the target, or `None` for null relative pointers
+    #[inline]
+    pub fn metadata(&self) -> Option<T::Data> {
+        self.data
+    }
+}
+
+impl<T: ?Sized + MetaData> Copy for TargetKey<T> {}
+impl<T: ?Sized + MetaData> Clone for TargetKey<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized + MetaData> Eq for TargetKey<T> {}
+impl<T: ?Sized + MetaData> PartialEq for TargetKey<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.addr == other.addr && self.data == other.data
+    }
+}
+
+impl<T: ?Sized + MetaData> Hash for TargetKey<T> where T::Data: Hash {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.addr.hash(state);
+        self.data.hash(state);
+    }
+}
+
+impl<T: ?Sized + MetaData> PartialOrd for TargetKey<T> where T::Data: Ord {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: ?Sized + MetaData> Ord for TargetKey<T> where T::Data: Ord {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.addr.cmp(&other.addr)
+            .then_with(|| self.data.cmp(&other.data))
+    }
+}
+
+impl<T: ?Sized + MetaData> std::fmt::Debug for TargetKey<T> where T::Data: std::fmt::Debug {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TargetKey")
+            .field("addr", &self.addr)
+            .field("metadata", &self.data)
+            .finish()
+    }
+}
+
+impl<T: ?Sized + MetaData, I: IntegerDelta> RelPtr<T, I> {
+    /**
+     * The address that the relative pointer points to, this does not
+     * check if the relative pointer is null
+     */
+    #[inline]
+    pub fn target_addr(&self) -> *const u8 {
+        (self as *const Self as *const u8).wrapping_offset(self.0.to_isize())
+    }
+
+    /**
+     * The resolved target of the relative pointer, see `TargetKey`
+     *
+     * A relative pointer with an offset of 0 is null
+     *
+     * # Safety
+     *
+     * The relative pointer must be null or have been successfully set
+     */
+    #[inline]
+    pub unsafe fn target_key(&self) -> TargetKey<T> {
+        if self.0.to_isize() == 0 {
+            TargetKey::null()
+        } else {
+            TargetKey {
+                addr: self.target_addr(),
+                data: Some(self.metadata()),
+                mark: PhantomData,
+            }
+        }
+    }
+
+    /**
+     * Check if two relative pointers point to the same target with the same metadata,
+     * unlike `==`, which checks if they are the same relative pointer
+     *
+     * Two null relative pointers point to the same target
+     *
+     * # Safety
+     *
+     * Both relative pointers must be null or have been successfully set
+     */
+    #[inline]
+    pub unsafe fn ptr_eq_target<J: IntegerDelta>(&self, other: &RelPtr<T, J>) -> bool {
+        self.target_key() == other.target_key()
+    }
+}
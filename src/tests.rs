@@ -1,5 +1,17 @@
 use super::*;
 
+/// The offset of a field, computed from raw pointers, because `core::mem::offset_of!`
+/// is newer than the minimum Rust version
+#[cfg(any(feature = "inspect", all(feature = "mmap", unix)))]
+macro_rules! offset_of {
+    ($type:ty, $field:ident) => {{
+        let value = core::mem::MaybeUninit::<$type>::uninit();
+        let base = value.as_ptr();
+
+        unsafe { (core::ptr::addr_of!((*base).$field) as *const u8).offset_from(base as *const u8) as usize }
+    }};
+}
+
 struct SelfRef<T, U: ?Sized + MetaData> {
     t_ref: RelPtr<U, i8>,
     t: T,
@@ -151,6 +163,1502 @@ fn check_copy() {
     }
 }
 
+#[test]
+fn tagged() {
+    struct Node {
+        value: u32,
+        ptr: TaggedRelPtr<u32, i32, 2>,
+    }
+
+    let mut node = Node { value: 10, ptr: TaggedRelPtr::null() };
+
+    assert!(node.ptr.is_null());
+
+    node.ptr.set_tag(2);
+    node.ptr.set(&mut node.value).unwrap();
+
+    assert!(!node.ptr.is_null());
+    assert_eq!(node.ptr.tag(), 2);
+
+    node.ptr.set_tag(3);
+
+    let node = block_opt(node);
+
+    assert_eq!(node.ptr.tag(), 3);
+    assert_eq!(unsafe { *node.ptr.as_ref_unchecked() }, 10);
+    assert_eq!(unsafe { node.ptr.as_ref() }, Some(&node.value));
+}
+
+#[test]
+fn tagged_misaligned() {
+    let mut value = (0u8, 0u8);
+    let mut ptr = TaggedRelPtr::<u8, isize, 1>::null();
+
+    assert!(ptr.set(&mut value.0).is_err());
+    assert!(ptr.is_null());
+}
+
+#[test]
+fn chain() {
+    struct Chain {
+        data: [u8; 4],
+        inner: RelPtr<[u8], i16>,
+        outer: RelPtr<RelPtr<[u8], i16>, i32>,
+        head: RelPtr<RelPtr<RelPtr<[u8], i16>, i32>, i8>,
+    }
+
+    let mut c = Chain {
+        data: [1, 2, 3, 4],
+        inner: RelPtr::null(),
+        outer: RelPtr::null(),
+        head: RelPtr::null(),
+    };
+
+    assert_eq!(unsafe { c.outer.resolve_chain::<[u8], _>() }, None);
+
+    c.inner.set(&mut c.data[1..]).unwrap();
+    c.outer.set(&mut c.inner).unwrap();
+
+    assert_eq!(unsafe { c.head.resolve_chain::<[u8], _>() }, None);
+
+    c.head.set(&mut c.outer).unwrap();
+
+    let c = block_opt(c);
+
+    assert_eq!(unsafe { c.outer.resolve_chain() }, Some(&[2, 3, 4][..]));
+    assert_eq!(unsafe { c.head.resolve_chain() }, Some(&[2, 3, 4][..]));
+    assert_eq!(unsafe { c.head.resolve_chain::<RelPtr<[u8], i16>, _>() }.map(|p| p as *const _), Some(&c.inner as *const _));
+
+    let mut hops = [None; 3];
+    let res = unsafe { c.head.resolve_chain_with(|hop| { hops[hop.depth] = Some(hop.to); Ok::<(), ()>(()) }) };
+
+    assert_eq!(res, Ok(Some(&[2, 3, 4][..])));
+    assert_eq!(hops[0], Some(&c.outer as *const _ as *const u8));
+    assert_eq!(hops[1], Some(&c.inner as *const _ as *const u8));
+    assert_eq!(hops[2], Some(&c.data[1] as *const u8));
+
+    let res = unsafe { c.head.resolve_chain_with::<[u8], _, _, _>(|hop| if hop.depth == 2 { Err("out of bounds") } else { Ok(()) }) };
+
+    assert_eq!(res, Err("out of bounds"));
+}
+
+#[test]
+fn project() {
+    struct Outer {
+        name: (&'static str, u8),
+        data: [u8; 3],
+    }
+
+    struct Holder {
+        value: Outer,
+        ptr: RelPtr<Outer, i8>,
+        field: RelPtr<[u8], i16>,
+    }
+
+    let mut h = Holder {
+        value: Outer { name: ("outer", 7), data: [1, 2, 3] },
+        ptr: RelPtr::null(),
+        field: RelPtr::null(),
+    };
+
+    h.ptr.set(&mut h.value).unwrap();
+
+    // the field can stay borrowed while it is projected to, no mutable reference is made
+    let data = &h.value.data;
+
+    unsafe {
+        assert_eq!(*project!(h.ptr, name.0), "outer");
+        assert_eq!(*project!(h.ptr, name.1), 7);
+
+        h.ptr.project_to(&mut h.field, |o| {
+            let data = std::ptr::addr_of!((*o).data) as *const u8;
+            std::ptr::slice_from_raw_parts(data.add(1), 2)
+        }).unwrap();
+    }
+
+    assert_eq!(data[1..], [2, 3]);
+
+    let h = block_opt(h);
+
+    assert_eq!(unsafe { h.field.as_ref_unchecked() }, [2, 3]);
+}
+
+#[test]
+fn slice_access() {
+    let mut s = SelfRef::new([0, 1, 2, 3, 4], |x| &mut x[1..]);
+    let null = RelPtr::<[u8], i8>::null();
+
+    unsafe {
+        assert_eq!(s.t_ref.len(), 4);
+        assert_eq!(s.t_ref.get(0), Some(&1));
+        assert_eq!(s.t_ref.get(4), None);
+        assert_eq!(s.t_ref.subslice(1..=2), Some(&[2, 3][..]));
+        assert_eq!(s.t_ref.subslice(2..5), None);
+        assert_eq!(s.t_ref.split_at(1), Some((&[1][..], &[2, 3, 4][..])));
+        assert_eq!(s.t_ref.split_at(5), None);
+
+        *s.t_ref.get_mut(3).unwrap() = 10;
+
+        assert!(null.is_empty());
+        assert_eq!(null.get(0), None);
+        assert_eq!(null.subslice(..), Some(&[][..]));
+    }
+
+    assert_eq!(*s.t(), [0, 1, 2, 3, 10]);
+}
+
+#[test]
+fn str_access() {
+    let s = SelfRef::new(*b"hello world", |x| unsafe { std::str::from_utf8_unchecked_mut(&mut x[6..]) });
+
+    unsafe {
+        assert_eq!(s.t_ref.len(), 5);
+        assert_eq!(s.t_ref.subslice(1..3), Some("or"));
+        assert_eq!(s.t_ref.subslice(3..6), None);
+        assert!(s.t_ref.chars().eq("world".chars()));
+        assert!(s.t_ref.bytes().eq(b"world".iter().copied()));
+    }
+}
+
+#[test]
+fn error_introspection() {
+    let mut value = ([0u8; 200], 0u8);
+    let mut ptr = RelPtr::<u8, i8>::null();
+
+    let err = ptr.set(&mut value.0[0]).unwrap_err();
+    let del = &mut value.0[0] as *mut u8 as isize - &ptr as *const _ as isize;
+
+    assert_eq!(err.kind(), IntegerDeltaErrorKind::Conversion);
+    assert_eq!(err.attempted_offset(), Some(del));
+    assert_eq!(Error::from(err).as_integer(), Some(&err));
+
+    assert_eq!(i8::from_isize(200).unwrap_err().required_bits(), Some(9));
+    assert_eq!(i8::from_isize(-129).unwrap_err().required_bits(), Some(9));
+    assert_eq!(i16::from_isize(-40000).unwrap_err().required_bits(), Some(17));
+
+    let err = NonZeroI8::from_isize(0).unwrap_err();
+
+    assert_eq!(err.kind(), IntegerDeltaErrorKind::InvalidNonZero);
+    assert_eq!(err.required_bits(), None);
+
+    // the difference doesn't fit in an `isize`, so no offset type is large enough
+    let err = i64::sub(isize::MIN as usize as *mut u8, 1 as *mut u8).unwrap_err();
+
+    assert_eq!(err.kind(), IntegerDeltaErrorKind::Overflow);
+    assert_eq!(err.attempted_offset(), None);
+    assert_eq!(err.required_bits(), None);
+}
+
+#[test]
+fn wide_offsets() {
+    let wide = (1_i128 << 64) | 5;
+
+    assert_eq!(5_i128.try_to_isize(), Ok(5));
+    assert_eq!(NonZeroI128::new(-5).unwrap().try_to_isize(), Ok(-5));
+
+    // offsets that don't fit in an `isize` are rejected instead of truncated
+    let err = wide.try_to_isize().unwrap_err();
+
+    assert_eq!(err.kind(), IntegerDeltaErrorKind::Conversion);
+    assert_eq!(err.attempted_offset(), None);
+    assert_eq!(err.required_bits(), None);
+    assert!(NonZeroI128::new(wide).unwrap().try_to_isize().is_err());
+    assert_eq!(wide.byte_offset(), None);
+}
+
+#[test]
+#[should_panic]
+fn wide_offset_to_isize() {
+    ((1_i128 << 64) | 5).to_isize();
+}
+
+#[test]
+fn auto_delta() {
+    struct Far {
+        ptr: RelPtr<u8, Auto>,
+        data: [u8; 300],
+        near: RelPtr<u8, Auto>,
+    }
+
+    let mut far = Far { ptr: RelPtr::null(), data: [0; 300], near: RelPtr::null() };
+
+    far.data[299] = 1;
+    far.ptr.set(&mut far.data[0]).unwrap();
+    far.near.set(&mut far.data[299]).unwrap();
+
+    let far = block_opt(far);
+
+    assert_eq!(unsafe { *far.ptr.as_ref_unchecked() }, 0);
+    assert_eq!(unsafe { *far.near.as_ref_unchecked() }, 1);
+
+    assert_eq!(Auto::from_isize(-128).unwrap(), Auto::I8(-128));
+    assert_eq!(Auto::from_isize(300).unwrap(), Auto::I16(300));
+    assert_eq!(Auto::from_isize(1 << 20).unwrap().bytes(), 4);
+    assert_eq!(Auto::from_isize(0).unwrap(), Auto::NULL);
+}
+
+#[test]
+fn select_delta() {
+    #[repr(C)]
+    struct Big<I: Delta> {
+        ptr: RelPtr<(u8, u8), I>,
+        data: [u8; 1000],
+        value: (u8, u8),
+    }
+
+    type Offset = select_delta!(Big<isize>, ptr, value);
+
+    assert_eq!(smallest_delta_bytes(127), 1);
+    assert_eq!(smallest_delta_bytes(-129), 2);
+    assert_eq!(smallest_delta_bytes(1 << 16), 4);
+    assert_eq!(std::mem::size_of::<select_delta!(Big<isize>, ptr, data)>(), 1);
+    assert_eq!(std::mem::size_of::<select_delta!(Big<isize>, data, value.1)>(), 2);
+    assert_eq!(std::mem::size_of::<Offset>(), 2);
+
+    let mut big = Big::<Offset> { ptr: RelPtr::null(), data: [0; 1000], value: (1, 2) };
+
+    big.ptr.set(&mut big.value).unwrap();
+
+    assert_eq!(unsafe { *big.ptr.as_ref_unchecked() }, (1, 2));
+}
+
+#[test]
+fn varint() {
+    use crate::varint::*;
+
+    for &offset in &[0, 1, -1, 63, -64, 64, 1000, -1000, isize::MAX, isize::MIN] {
+        let mut buf = [0; MAX_VARINT_LEN];
+        let mut writer = VarintWriter::new(&mut buf);
+
+        writer.write_offset(offset).unwrap();
+
+        assert_eq!(writer.position(), encoded_len(offset));
+        assert_eq!(VarintReader::new(&buf).read_offset(), Ok(offset));
+    }
+
+    assert_eq!(encoded_len(-64), 1);
+    assert_eq!(encoded_len(64), 2);
+
+    let mut buf = [0; 8];
+    let mut writer = VarintWriter::new(&mut buf);
+
+    writer.write_bytes(b"abc").unwrap();
+    writer.write_rel(1).unwrap();
+    writer.write_rel(300).unwrap();
+
+    assert_eq!(writer.write_bytes(&[0; 8]), Err(VarintError::BufferFull));
+
+    let mut reader = VarintReader::new(&buf);
+
+    reader.seek(3);
+
+    assert_eq!(reader.read_rel(), Ok(1));
+    assert_eq!(reader.read_rel(), Err(VarintError::OutOfBounds(300)));
+    assert_eq!(reader.position(), 4);
+    assert_eq!(VarintReader::new(&[0x80, 0x80]).read_offset(), Err(VarintError::UnexpectedEnd));
+    assert_eq!(VarintReader::new(&[0xff; 11]).read_offset(), Err(VarintError::Overflow));
+}
+
+#[test]
+fn index_in() {
+    struct Table {
+        items: [u16; 4],
+        ptr: RelPtr<u16, i8>,
+    }
+
+    let mut t = Table { items: [1, 2, 3, 4], ptr: RelPtr::null() };
+
+    unsafe {
+        assert_eq!(t.ptr.index_in(&t.items), None);
+
+        t.ptr.set(&mut t.items[2]).unwrap();
+
+        assert_eq!(t.ptr.index_in(&t.items), Some(2));
+        assert_eq!(t.ptr.index_in(&t.items[..2]), None);
+        assert_eq!(t.ptr.index_in(&t.items[1..]), Some(1));
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde {
+    use super::*;
+    use crate::serde::*;
+
+    #[derive(::serde::Serialize, ::serde::Deserialize)]
+    struct Table {
+        items: [u32; 3],
+        selected: usize,
+    }
+
+    struct Indexed {
+        table: Table,
+        ptr: RelPtr<u32>,
+    }
+
+    impl SelfReferential for Indexed {
+        type Data = Table;
+        type Error = IntegerDeltaError;
+
+        fn data(&self) -> &Table {
+            &self.table
+        }
+
+        fn from_data(table: Table) -> Result<Self, Self::Error> {
+            let mut this = Self { table, ptr: RelPtr::null() };
+
+            this.ptr.set(&mut this.table.items[this.table.selected])?;
+
+            Ok(this)
+        }
+    }
+
+    #[derive(::serde::Serialize, ::serde::Deserialize)]
+    struct Outer {
+        #[serde(with = "crate::serde::self_ref")]
+        inner: Indexed,
+    }
+
+    #[test]
+    fn round_trip() {
+        let outer = Outer {
+            inner: Indexed::from_data(Table { items: [1, 2, 3], selected: 1 }).unwrap(),
+        };
+
+        let json = serde_json::to_string(&outer).unwrap();
+
+        assert_eq!(json, r#"{"inner":{"items":[1,2,3],"selected":1}}"#);
+
+        let outer: Outer = block_opt(serde_json::from_str(&json).unwrap());
+
+        assert_eq!(unsafe { *outer.inner.ptr.as_ref_unchecked() }, 2);
+
+        let json = serde_json::to_string(&Persist(outer.inner)).unwrap();
+
+        assert_eq!(json, r#"{"items":[1,2,3],"selected":1}"#);
+    }
+
+    #[test]
+    fn serialize_target() {
+        let s = SelfRef::new((1u8, "hi"), id);
+        let null = RelPtr::<u8>::null();
+
+        unsafe {
+            assert_eq!(serde_json::to_string(&SerializeTarget::new(&s.t_ref)).unwrap(), r#"[1,"hi"]"#);
+            assert_eq!(serde_json::to_string(&SerializeTarget::new(&null)).unwrap(), "null");
+        }
+    }
+}
+
+#[test]
+fn rel_trace() {
+    type Rest = (Option<RelPtr<[u32], i16>>, [RelPtr<u32, i8>; 2]);
+
+    struct Graph {
+        values: [u32; 3],
+        first: RelPtr<u32, i16>,
+        rest: Rest,
+    }
+
+    impl_rel_trace!(Graph { first, rest });
+
+    struct Targets([usize; 4], usize);
+
+    impl RelVisitor for Targets {
+        fn visit<T: ?Sized + MetaData, I: IntegerDelta>(&mut self, ptr: &RelPtr<T, I>) {
+            self.0[self.1] = (ptr as *const _ as isize + ptr.offset().to_isize()) as usize;
+            self.1 += 1;
+        }
+    }
+
+    struct Nullify;
+
+    impl RelVisitorMut for Nullify {
+        fn visit_mut<T: ?Sized + MetaData, I: IntegerDelta>(&mut self, ptr: &mut RelPtr<T, I>) {
+            *ptr = RelPtr::from(I::from_isize(0).unwrap());
+        }
+    }
+
+    let mut g = Graph {
+        values: [1, 2, 3],
+        first: RelPtr::null(),
+        rest: (Some(RelPtr::null()), [RelPtr::null(); 2]),
+    };
+
+    g.first.set(&mut g.values[0]).unwrap();
+    g.rest.0.as_mut().unwrap().set(&mut g.values[1..]).unwrap();
+    g.rest.1[0].set(&mut g.values[2]).unwrap();
+    g.rest.1[1].set(&mut g.values[2]).unwrap();
+
+    let mut targets = Targets([0; 4], 0);
+    let values = &g.values as *const u32 as usize;
+
+    g.trace(&mut targets);
+
+    assert_eq!(targets.1, 4);
+    assert_eq!(targets.0, [values, values + 4, values + 8, values + 8]);
+
+    g.trace_mut(&mut Nullify);
+
+    assert!(g.first.is_null());
+    assert!(g.rest.0.unwrap().is_null());
+    assert!(g.rest.1[1].is_null());
+
+    // the pointee doesn't need to implement `RelTrace`
+    #[cfg(not(feature = "no_std"))]
+    {
+        let mut name = (String::from("name"), RelPtr::<String, i16>::null());
+
+        name.1.set(&mut name.0).unwrap();
+
+        let mut targets = Targets([0; 4], 0);
+
+        name.1.trace(&mut targets);
+
+        assert_eq!(targets.1, 1);
+        assert_eq!(targets.0[0], &name.0 as *const String as usize);
+    }
+}
+
+#[test]
+fn rebase_block() {
+    struct Entry {
+        inner: RelPtr<u8, i8>,
+        outer: RelPtr<u8, i16>,
+        value: u8,
+    }
+
+    impl_rel_trace!(Entry { inner, outer });
+
+    let mut arena = ([0u8; 16], [Entry { inner: RelPtr::null(), outer: RelPtr::null(), value: 1 }]);
+    let (data, entry) = &mut arena;
+
+    data[3] = 7;
+    entry[0].inner.set(&mut entry[0].value).unwrap();
+    entry[0].outer.set(&mut data[3]).unwrap();
+
+    let old = entry as *const _ as *const u8;
+    let len = std::mem::size_of_val(entry);
+    let mut moved = [unsafe { std::ptr::read(&entry[0]) }];
+    let new = &moved as *const _ as *const u8;
+
+    rebase(&mut moved, old, new, len).unwrap();
+
+    assert_eq!(unsafe { *moved[0].inner.as_ref_unchecked() }, 1);
+    assert_eq!(unsafe { *moved[0].outer.as_ref_unchecked() }, 7);
+
+    let mut block = [Entry { inner: RelPtr::null(), outer: RelPtr::from(-30_000), value: 2 }];
+    let at = &block as *const _ as *const u8;
+
+    // the original pointee is out of range for `i16` after the move
+    let err = rebase(&mut block, unsafe { at.sub(30_000) }, at, len).unwrap_err();
+
+    assert_eq!(err.kind(), IntegerDeltaErrorKind::Conversion);
+    assert_eq!(block[0].outer.offset(), -30_000);
+}
+
+#[test]
+fn rebase_tagged() {
+    #[repr(C, align(4))]
+    struct Entry {
+        value: u32,
+        inner: TaggedRelPtr<u32, i32, 2>,
+        outer: TaggedRelPtr<u32, i32, 2>,
+    }
+
+    impl_rel_trace!(Entry { inner, outer });
+
+    let mut outside = 7_u32;
+    let mut entry = Entry { value: 1, inner: TaggedRelPtr::null(), outer: TaggedRelPtr::null() };
+
+    entry.inner.set(&mut entry.value).unwrap();
+    entry.inner.set_tag(1);
+    entry.outer.set(&mut outside).unwrap();
+    entry.outer.set_tag(3);
+
+    let old = &entry as *const Entry;
+    let mut moved = unsafe { std::ptr::read(&entry) };
+
+    rebase_moved(&mut moved, old).unwrap();
+
+    assert_eq!(moved.inner.tag(), 1);
+    assert_eq!(moved.outer.tag(), 3);
+    assert_eq!(unsafe { *moved.inner.as_ref_unchecked() }, 1);
+    assert_eq!(unsafe { *moved.outer.as_ref_unchecked() }, 7);
+
+    // the new offset has to leave the low bits free for the tag
+    let at = &moved as *const Entry as *const u8;
+    let err = rebase(&mut moved, unsafe { at.sub(2) }, at, std::mem::size_of::<Entry>()).unwrap_err();
+
+    assert_eq!(err.kind(), IntegerDeltaErrorKind::Misaligned);
+    assert_eq!(moved.outer.tag(), 3);
+    assert_eq!(unsafe { *moved.outer.as_ref_unchecked() }, 7);
+}
+
+#[test]
+fn target_eq() {
+    let mut data = ([1_u8, 2, 3], [RelPtr::<[u8], i8>::null(); 3], RelPtr::<[u8], i64>::null());
+    let (items, ptrs, wide) = &mut data;
+
+    ptrs[0].set(&mut items[..2]).unwrap();
+    ptrs[1].set(&mut items[..2]).unwrap();
+    ptrs[2].set(&mut items[..]).unwrap();
+    wide.set(&mut items[..2]).unwrap();
+
+    unsafe {
+        assert!(ptrs[0] != ptrs[1]);
+        assert!(ptrs[0].ptr_eq_target(&ptrs[1]));
+        assert!(ptrs[0].ptr_eq_target(wide));
+        assert!(!ptrs[0].ptr_eq_target(&ptrs[2]));
+        assert!(!ptrs[0].ptr_eq_target(&RelPtr::<[u8], i8>::null()));
+        assert!(RelPtr::<[u8], i8>::null().ptr_eq_target(&RelPtr::<[u8], i16>::null()));
+
+        assert_eq!(ptrs[0].target_key(), TargetKey::of(&items[..2]));
+        assert_eq!(ptrs[0].target_key().metadata(), Some(2));
+        assert!(ptrs[0].target_key() < ptrs[2].target_key());
+        assert!(RelPtr::<[u8], i8>::null().target_key().is_null());
+    }
+}
+
+#[test]
+fn convert_delta() {
+    use std::convert::TryFrom;
+
+    let mut data = ([1_u8, 2, 3], RelPtr::<[u8], i16>::null());
+    data.1.set(&mut data.0[1..]).unwrap();
+
+    // in the same place, the converted relative pointer points to the same slice
+    let narrow = RelPtr::<[u8], i8>::try_from(data.1).unwrap();
+    let wide = RelPtr::<[u8], i64>::from(narrow);
+    assert_eq!(wide.offset(), data.1.offset() as i64);
+    assert_eq!(unsafe { wide.metadata() }, 2);
+
+    let auto = RelPtr::<u8, Auto>::from(RelPtr::<u8, i32>::from(70_000));
+    assert_eq!(auto.offset(), Auto::I32(70_000));
+    assert!(RelPtr::<u8, i16>::try_from(auto).is_err());
+    assert_eq!(RelPtr::<u8, i64>::from(auto).offset(), 70_000);
+
+    let nonzero = RelPtr::<u8, NonZeroI8>::try_from(RelPtr::<u8, i64>::from(-5)).unwrap();
+    assert_eq!(RelPtr::<u8, NonZeroI16>::from(nonzero).offset().get(), -5);
+    assert_eq!(RelPtr::<u8, isize>::from(nonzero).offset(), -5);
+
+    let err = RelPtr::<u8, NonZeroI32>::try_from(RelPtr::<u8, i8>::null()).unwrap_err();
+    assert_eq!(err.kind(), IntegerDeltaErrorKind::InvalidNonZero);
+
+    let err = RelPtr::<u8, i8>::from(1).cast_delta::<NonZeroI8>().map(|ptr| ptr.offset());
+    assert_eq!(err, Ok(NonZeroI8::new(1).unwrap()));
+
+    // a 128-bit offset that doesn't fit in an `isize` is never truncated
+    let wide = (1_i128 << 64) | 5;
+
+    let err = RelPtr::<u8, i8>::try_from(RelPtr::<u8, i128>::from(wide)).unwrap_err();
+    assert_eq!(err.kind(), IntegerDeltaErrorKind::Conversion);
+    assert!(RelPtr::<u8, i128>::from(wide).cast_delta::<i64>().is_err());
+    assert!(RelPtr::<u8, Auto>::try_from(RelPtr::<u8, NonZeroI128>::from(NonZeroI128::new(wide).unwrap())).is_err());
+
+    // but it can be converted between the 128-bit offsets
+    let nonzero = RelPtr::<u8, NonZeroI128>::try_from(RelPtr::<u8, i128>::from(wide)).unwrap();
+    assert_eq!(RelPtr::<u8, i128>::from(nonzero).offset(), wide);
+    assert!(RelPtr::<u8, NonZeroI128>::try_from(RelPtr::<u8, i128>::null()).is_err());
+}
+
+#[test]
+fn cast_unsize() {
+    union Slot {
+        array: RelPtr<[u16; 3], i8>,
+        slice: RelPtr<[u16], i8>,
+        first: RelPtr<u16, i8>,
+    }
+
+    let mut data = ([1_u16, 2, 3], Slot { array: RelPtr::null() });
+
+    unsafe {
+        data.1.array.set(&mut data.0).unwrap();
+
+        // in the same place, the converted relative pointers point to the same value
+        data.1.slice = data.1.array.unsize_slice();
+        assert_eq!(data.1.slice.as_ref_unchecked(), [1, 2, 3]);
+
+        data.1.first = data.1.slice.cast();
+        assert_eq!(*data.1.first.as_ref_unchecked(), 1);
+
+        data.1.slice = data.1.first.cast::<[u16; 2]>().unsize();
+        assert_eq!(data.1.slice.as_ref_unchecked(), [1, 2]);
+    }
+}
+
+#[test]
+fn copy_to() {
+    let mut data = ([0_u8; 300], RelPtr::<str, i16>::null(), [RelPtr::<str, i8>::null(); 2]);
+    let (buffer, ptr, near) = &mut data;
+
+    buffer[..5].copy_from_slice(b"hello");
+    ptr.set(std::str::from_utf8_mut(&mut buffer[..5]).unwrap()).unwrap();
+
+    let mut far = RelPtr::<str, i64>::null();
+    ptr.copy_to(&mut far).unwrap();
+    assert_eq!(unsafe { far.as_ref_unchecked() }, "hello");
+
+    // the target is more than 300 bytes from `near`
+    let err = ptr.copy_to(&mut near[0]).unwrap_err();
+    assert_eq!(err.as_integer().map(IntegerDeltaError::kind), Some(IntegerDeltaErrorKind::Conversion));
+    assert!(near[0].is_null());
+
+    near[0].set(std::str::from_utf8_mut(&mut buffer[295..]).unwrap()).unwrap();
+    let (first, second) = near.split_at_mut(1);
+    first[0].copy_to(&mut second[0]).unwrap();
+    assert_eq!(unsafe { near[1].as_ref_unchecked() }, "\0\0\0\0\0");
+
+    let mut nonzero = RelPtr::<str, NonZeroI64>::from(NonZeroI64::new(1).unwrap());
+    RelPtr::<str, i8>::null().copy_to(&mut far).unwrap();
+    assert!(far.is_null());
+    assert!(RelPtr::<str, i8>::null().copy_to(&mut nonzero).is_err());
+}
+
+#[test]
+fn rel_const_mut() {
+    fn send_sync<T: Send + Sync>() {}
+
+    send_sync::<RelConst<u32, i8>>();
+    send_sync::<RelMut<[u32], i8>>();
+
+    let mut data = ([1_u32, 2, 3], RelMut::<[u32], i8>::null(), RelConst::<u32, i8>::null());
+    let (items, slice, last) = &mut data;
+
+    assert!(slice.is_null() && unsafe { slice.as_ref() }.is_none());
+
+    slice.set(&mut items[1..]).unwrap();
+    last.set(&items[2]).unwrap();
+
+    unsafe {
+        slice.as_mut_unchecked()[0] = 20;
+
+        // the views are in the same place, so they point to the same values
+        assert_eq!(slice.as_const().as_ref_unchecked(), [20, 3]);
+        assert_eq!(RelMut::from_rel_ptr_mut(slice.as_rel_ptr_mut()).as_ref(), Some(&[20, 3][..]));
+        assert_eq!(*RelConst::from_rel_ptr(last.as_rel_ptr()).as_ref_unchecked(), 3);
+    }
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn thread_safety() {
+    use std::sync::Arc;
+
+    struct Table {
+        values: [u32; 4],
+        max: RelConst<u32, i8>,
+        tail: RelMut<[u32], i8>,
+    }
+
+    fn send_sync<T: Send + Sync>(value: T) -> T {
+        value
+    }
+
+    let mut table = Box::new(Table { values: [3, 9, 4, 1], max: RelConst::null(), tail: RelMut::null() });
+    table.max.set(&table.values[1]).unwrap();
+    table.tail.set(&mut table.values[2..]).unwrap();
+
+    // a worker can mutate through the `RelMut` after the table is moved to it
+    let mut table = std::thread::spawn(move || {
+        unsafe { table.tail.as_mut_unchecked()[1] = 10 };
+        table
+    }).join().unwrap();
+
+    unsafe { table.max.set(table.tail.as_ref_unchecked().last().unwrap()).unwrap() };
+
+    // and many workers can read through the `RelConst` while it is shared
+    let table = Arc::new(send_sync(*table));
+    let workers = (0..4).map(|_| {
+        let table = Arc::clone(&table);
+        std::thread::spawn(move || unsafe { *table.max.as_ref_unchecked() })
+    }).collect::<Vec<_>>();
+
+    for worker in workers {
+        assert_eq!(worker.join().unwrap(), 10);
+    }
+}
+
+#[test]
+fn rel_fn() {
+    fn double(x: u32) -> u32 {
+        x * 2
+    }
+
+    fn square(x: u32) -> u32 {
+        x * x
+    }
+
+    extern "C" fn negate(x: i32) -> i32 {
+        -x
+    }
+
+    let mut table = [RelFn::<fn(u32) -> u32, i64>::null(); 2];
+    table[0].set(double).unwrap();
+
+    let (first, second) = table.split_at_mut(1);
+    first[0].copy_to(&mut second[0]).unwrap();
+
+    unsafe {
+        assert_eq!(table[1].get().map(|f| f(5)), Some(10));
+        assert_eq!(table[0].get_unchecked()(7), 14);
+    }
+
+    // code is far from the stack
+    let mut near = RelFn::<fn(u32) -> u32, i8>::null();
+    assert!(near.set(square).is_err());
+    assert!(near.is_null());
+    assert!(RelFn::<fn(u32) -> u32, i64>::null().copy_to(&mut table[0]).is_ok());
+    assert!(table[0].is_null());
+
+    let mut c_fn = RelFn::<extern "C" fn(i32) -> i32, isize>::null();
+    c_fn.set(negate).unwrap();
+    assert_eq!(unsafe { c_fn.get_unchecked() }(3), -3);
+
+    // it is just the offset, so it can be stored wherever the offset can
+    assert_eq!(core::mem::size_of::<RelFn<fn(), i16>>(), 2);
+    assert_eq!(core::mem::align_of::<RelFn<fn(), i16>>(), 2);
+
+    let mut vtable = RelVTable::<fn(u32) -> u32, i64, 2>::new([double, double]).unwrap();
+    vtable.set(1, square).unwrap();
+
+    // the table is plain data, so it can be copied anywhere
+    let offsets = *vtable.offsets();
+    let copy = RelVTable::<fn(u32) -> u32, i64, 2>::from_offsets(offsets);
+
+    unsafe {
+        assert_eq!(copy.get(0).unwrap()(6), 12);
+        assert_eq!(copy.get(1).unwrap()(6), 36);
+        assert!(copy.get(2).is_none());
+    }
+
+    let nonzero = RelVTable::<fn(u32) -> u32, NonZeroIsize, 1>::new([square]).unwrap();
+    assert_eq!(unsafe { nonzero.get(0).unwrap()(4) }, 16);
+    assert_eq!(nonzero.len(), 1);
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn rel_graph_vec() {
+    let mut graph = RelGraphVec::<u64, i16>::with_capacity(1);
+
+    // a ring of 0 -> 1 -> 2 -> 3 -> 0, and 4 links to itself
+    for value in 0..5 {
+        graph.push(value * 10);
+    }
+
+    for from in 0..4 {
+        graph.link(from, (from + 1) % 4).unwrap();
+    }
+
+    graph.link(4, 4).unwrap();
+    graph.reserve(1000);
+
+    assert_eq!((0..5).map(|i| graph.follow(i)).collect::<Vec<_>>(), [Some(1), Some(2), Some(3), Some(0), Some(4)]);
+
+    // the links are real relative pointers, so they resolve in place
+    let link = unsafe { &*((&graph[4] as *const u64).sub(1) as *const RelPtr<u64, i16>) };
+    assert_eq!(unsafe { *link.as_ref_unchecked() }, 40);
+
+    // 4 moves into the place of 1, and links to itself there, links to 1 become null
+    assert_eq!(graph.swap_remove(1), Ok(10));
+    assert_eq!((0..4).map(|i| graph.follow(i)).collect::<Vec<_>>(), [None, Some(1), Some(3), Some(0)]);
+    assert_eq!(graph.linked(1), Some(&40));
+
+    *graph.linked_mut(3).unwrap() += 1;
+    assert_eq!(graph.pop(), Some(30));
+    assert_eq!(graph.follow(2), None);
+
+    let clone = graph.clone();
+    assert_eq!(format!("{:?}", clone), "[(1, None), (40, Some(1)), (20, None)]");
+
+    // offsets that don't fit are rejected without changing anything
+    let mut wide = RelGraphVec::<[u8; 69], i8>::new();
+    for value in 0..4 {
+        wide.push([value; 69]);
+    }
+    assert!(wide.link(0, 3).is_err());
+    assert!(wide.link(3, 2).is_ok());
+    assert!(wide.swap_remove(0).is_err());
+    assert_eq!((wide.len(), wide.follow(3)), (4, Some(2)));
+}
+
+#[test]
+fn offset_arith() {
+    let mut data = ([1_u16, 2, 3, 4], RelPtr::<u16, i8>::null(), RelPtr::<[u16], i8>::null());
+    let (items, ptr, slice) = &mut data;
+
+    ptr.set(&mut items[1]).unwrap();
+    let offset = ptr.offset();
+
+    ptr.offset_by(2).unwrap();
+    assert_eq!(unsafe { *ptr.as_ref_unchecked() }, 4);
+    ptr.offset_by(-3).unwrap();
+    assert_eq!(unsafe { *ptr.as_ref_unchecked() }, 1);
+    ptr.byte_offset_by(2).unwrap();
+    assert_eq!(ptr.offset(), offset);
+
+    let err = ptr.offset_by(100).unwrap_err();
+    assert_eq!(err.kind(), IntegerDeltaErrorKind::Conversion);
+    assert_eq!(ptr.offset(), offset);
+
+    let err = ptr.offset_by(isize::MAX).unwrap_err();
+    assert_eq!(err.kind(), IntegerDeltaErrorKind::Overflow);
+    assert_eq!(err.required_bits(), None);
+
+    let mut wrapping = RelPtr::<u16, i8>::from(100);
+    wrapping.wrapping_offset_by(50);
+    assert_eq!(wrapping.offset(), -56);
+    wrapping.wrapping_byte_offset_by(-100);
+    assert_eq!(wrapping.offset(), 100);
+
+    let mut nonzero = RelPtr::<u8, NonZeroI8>::from(NonZeroI8::new(3).unwrap());
+    assert_eq!(nonzero.offset_by(-3).unwrap_err().kind(), IntegerDeltaErrorKind::InvalidNonZero);
+
+    // a 128-bit offset that doesn't fit in an `isize` is an error, not truncated
+    let wide = (1_i128 << 64) | 5;
+    let mut wide_ptr = RelPtr::<u8, i128>::from(wide);
+    assert_eq!(wide_ptr.offset_by(1).unwrap_err().kind(), IntegerDeltaErrorKind::Conversion);
+    assert_eq!(wide_ptr.offset(), wide);
+
+    slice.set(&mut items[..]).unwrap();
+
+    unsafe {
+        slice.advance(0).unwrap();
+        assert_eq!(slice.as_ref_unchecked(), [1, 2, 3, 4]);
+        slice.advance(1).unwrap();
+        assert_eq!(slice.as_ref_unchecked(), [2, 3, 4]);
+        slice.wrapping_advance(2);
+        assert_eq!(slice.as_ref_unchecked(), [4]);
+        slice.advance(1).unwrap();
+        assert!(slice.is_empty());
+
+        let mut null = RelPtr::<[u16], i8>::null();
+        null.advance(0).unwrap();
+        assert!(null.is_null());
+    }
+}
+
+#[test]
+#[should_panic(expected = "tried to advance by 5 elements, but the length is 4")]
+fn offset_arith_advance_past_end() {
+    let mut data = ([1_u16, 2, 3, 4], RelPtr::<[u16], i8>::null());
+
+    data.1.set(&mut data.0[..]).unwrap();
+
+    unsafe { data.1.advance(5).unwrap() };
+}
+
+#[test]
+fn delta_laws() {
+    use crate::laws::*;
+
+    macro_rules! check {
+        ($($type:ty),*) => {$(
+            check_delta_laws::<$type>();
+            check_integer_delta_laws::<$type>();
+        )*};
+    }
+
+    macro_rules! check_nullable {
+        ($($type:ty),*) => {$(
+            check!($type);
+            check_nullable_laws::<$type>();
+        )*};
+    }
+
+    check_nullable!(i8, i16, i32, i64, i128, isize, Auto);
+    check!(NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize);
+}
+
+#[test]
+#[should_panic(expected = "add(sub(a, b), b) != a")]
+fn delta_laws_broken() {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    struct OffByOne(isize);
+
+    unsafe impl Delta for OffByOne {
+        type Error = ();
+
+        fn sub(a: *mut u8, b: *mut u8) -> Result<Self, ()> {
+            Ok(OffByOne(a as isize - b as isize))
+        }
+
+        unsafe fn sub_unchecked(a: *mut u8, b: *mut u8) -> Self {
+            OffByOne(a as isize - b as isize)
+        }
+
+        unsafe fn add(self, a: *const u8) -> *mut u8 {
+            (a as *mut u8).wrapping_offset(self.0 + 1)
+        }
+    }
+
+    crate::laws::check_delta_laws::<OffByOne>();
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn debug_fmt() {
+    use crate::debug::{DisplayTarget, WithMetadata};
+
+    let mut data = ([1_u8, 2, 3], RelPtr::<[u8], i8>::null());
+
+    let null = format!("{:#?}", data.1);
+    assert!(null.contains("null: true"));
+    assert!(!format!("{:?}", data.1).contains("target"));
+
+    data.1.set(&mut data.0[1..]).unwrap();
+
+    let set = format!("{:#?}", data.1);
+    assert!(set.contains("null: false"));
+    assert!(set.contains("target: "));
+    assert_eq!(data.1.target_addr(), &data.0[1] as *const u8);
+
+    // the metadata is never read, so pointers that were never set can be formatted
+    assert!(format!("{:#?}", RelPtr::<[u8], i8>::from(4)).contains("null: false"));
+
+    // offsets that aren't a plain number of bytes only show the offset
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+    struct Opaque;
+
+    unsafe impl Delta for Opaque {
+        type Error = ();
+
+        fn sub(_: *mut u8, _: *mut u8) -> Result<Self, ()> {
+            Err(())
+        }
+
+        unsafe fn sub_unchecked(_: *mut u8, _: *mut u8) -> Self {
+            Opaque
+        }
+
+        unsafe fn add(self, a: *const u8) -> *mut u8 {
+            a as *mut u8
+        }
+    }
+
+    let opaque = format!("{:#?}", RelPtr::<[u8], Opaque>::from(Opaque));
+    assert!(opaque.contains("offset: Opaque") && !opaque.contains("target"));
+
+    assert_eq!(format!("{:?}", unsafe { DisplayTarget::new(&data.1) }), "[2, 3]");
+
+    let meta = format!("{:#?}", unsafe { WithMetadata::new(&data.1) });
+    assert!(meta.contains("null: false") && meta.contains("metadata: 2"));
+
+    let null = format!("{:?}", unsafe { WithMetadata::new(&RelPtr::<[u8], i8>::null()) });
+    assert!(null.contains("null: true") && !null.contains("metadata"));
+}
+
+#[cfg(not(feature = "no_std"))]
+#[test]
+fn rel_clone() {
+    #[derive(Clone)]
+    struct Doc {
+        id: u32,
+        text: String,
+        items: Vec<u16>,
+        id_ptr: RelPtr<u32, i64>,
+        word: RelPtr<str, i64>,
+        item: RelPtr<u16, i64>,
+        shared: RelPtr<u8, i64>,
+    }
+
+    impl_rel_clone!(Doc { ptrs: [id_ptr, word, item, shared], owns: [text, items] });
+
+    let mut shared = 9u8;
+
+    let mut doc = Box::new(Doc {
+        id: 3,
+        text: "abc def".to_string(),
+        items: vec![1, 2, 3],
+        id_ptr: RelPtr::null(),
+        word: RelPtr::null(),
+        item: RelPtr::null(),
+        shared: RelPtr::null(),
+    });
+
+    let doc = &mut *doc;
+    doc.id_ptr.set(&mut doc.id).unwrap();
+    doc.word.set(&mut doc.text[4..]).unwrap();
+    doc.item.set(&mut doc.items[2]).unwrap();
+    doc.shared.set(&mut shared).unwrap();
+
+    let copy = doc.rel_clone_boxed().unwrap();
+
+    doc.id = 0;
+    doc.text.make_ascii_uppercase();
+    doc.items[2] = 0;
+
+    unsafe {
+        assert_eq!(*copy.id_ptr.as_ref_unchecked(), 3);
+        assert_eq!(copy.word.as_ref_unchecked(), "def");
+        assert_eq!(*copy.item.as_ref_unchecked(), 3);
+        assert_eq!(copy.shared.as_ref_unchecked() as *const u8, &shared as *const u8);
+    }
+}
+
+#[test]
+fn rel_clone_adjacent() {
+    #[derive(Clone)]
+    struct Link {
+        value: u32,
+        next: RelPtr<Link, i64>,
+    }
+
+    impl_rel_clone!(Link { ptrs: [next], owns: [] });
+
+    let mut links = [Link { value: 1, next: RelPtr::null() }, Link { value: 2, next: RelPtr::null() }];
+    let (first, rest) = links.split_at_mut(1);
+
+    first[0].next.set(&mut rest[0]).unwrap();
+
+    // the next link starts right after the cloned one, so it isn't part of the clone
+    let mut dest = core::mem::MaybeUninit::uninit();
+    let copy = links[0].rel_clone_into(&mut dest).unwrap();
+
+    assert_eq!(unsafe { copy.next.as_ref_unchecked() } as *const Link, &links[1] as *const Link);
+    assert_eq!(unsafe { copy.next.as_ref_unchecked() }.value, 2);
+}
+
+#[cfg(feature = "inspect")]
+mod inspect {
+    use super::*;
+    use crate::inspect::*;
+
+    const SCHEMA: &str = "
+        // a list of named values
+        struct Node {
+            value: u32,
+            next: ptr<i32, Node>,
+            name: str<i32>, # the name is stored after the nodes
+        }
+
+        struct Image {
+            nodes: [Node; 2],
+            text: [u8; 8],
+        }
+    ";
+
+    #[repr(C)]
+    struct Node {
+        value: u32,
+        next: RelPtr<Node, i32>,
+        name: RelPtr<str, i32>,
+    }
+
+    #[repr(C)]
+    struct Image {
+        nodes: [Node; 2],
+        text: [u8; 8],
+    }
+
+    fn image() -> Vec<u8> {
+        let mut image = std::mem::MaybeUninit::<Image>::zeroed();
+        let image = unsafe { &mut *image.as_mut_ptr() };
+
+        image.text = *b"ab cdefg";
+
+        let text = &mut image.text;
+        let (first, second) = image.nodes.split_at_mut(1);
+
+        first[0].value = 1;
+        first[0].next.set(&mut second[0]).unwrap();
+        first[0].name.set(std::str::from_utf8_mut(&mut text[..2]).unwrap()).unwrap();
+        second[0].value = 2;
+        second[0].name.set(std::str::from_utf8_mut(&mut text[3..]).unwrap()).unwrap();
+
+        unsafe {
+            std::slice::from_raw_parts(image as *const Image as *const u8, std::mem::size_of::<Image>()).to_vec()
+        }
+    }
+
+    fn next_pos(schema: &Schema) -> usize {
+        let node = &schema.structs()[schema.find("Node").unwrap()];
+        node.fields.iter().find(|field| field.name == "next").unwrap().offset
+    }
+
+    fn set_next(image: &mut [u8], pos: usize, offset: i32) {
+        image[pos..pos + 4].copy_from_slice(&offset.to_ne_bytes());
+    }
+
+    #[test]
+    fn schema_layout() {
+        let schema = Schema::parse(SCHEMA).unwrap();
+        let node = &schema.structs()[0];
+
+        assert_eq!(node.size, std::mem::size_of::<Node>());
+        assert_eq!(node.align, std::mem::align_of::<Node>());
+        assert_eq!(next_pos(&schema), 4);
+        assert_eq!(schema.structs()[1].size, std::mem::size_of::<Image>());
+
+        assert!(Schema::parse("struct A { a: B }").is_err());
+        assert!(Schema::parse("struct A { a: A }").is_err());
+        assert!(Schema::parse("struct A { a: ptr<u8, A> }").is_err());
+        assert!(Schema::parse("struct A { a: u8 b: u8 }").is_err());
+        assert!(Schema::parse("struct A { a: ptr<i8, A>, b: [slice<i8, u8>; 2] }").is_ok());
+
+        // the sizes of arrays and structs are checked
+        assert!(Schema::parse("struct A { a: [u64; 4611686018427387904] }").is_err());
+        assert!(Schema::parse("struct A { a: [[u8; 4294967296]; 4294967296] }").is_err());
+        assert!(Schema::parse("struct A { a: [u8; 9223372036854775807], b: [u8; 2] }").is_err());
+        assert!(Schema::parse("struct A { a: ptr<i8, [u64; 4611686018427387904]> }").is_err());
+        assert!(Schema::parse("struct A { a: slice<i8, ptr<i8, [u64; 4611686018427387904]>> }").is_err());
+
+        // primitives use their real alignment, which can be smaller than their size
+        #[repr(C)]
+        struct Prims {
+            a: u8,
+            b: u64,
+            c: u16,
+            d: f64,
+            e: char,
+        }
+
+        let prims = Schema::parse("struct Prims { a: u8, b: u64, c: u16, d: f64, e: char }").unwrap();
+        let prims = &prims.structs()[0];
+
+        assert_eq!(prims.size, std::mem::size_of::<Prims>());
+        assert_eq!(prims.align, std::mem::align_of::<Prims>());
+        assert_eq!(prims.fields[1].offset, offset_of!(Prims, b));
+        assert_eq!(prims.fields[3].offset, offset_of!(Prims, d));
+    }
+
+    #[test]
+    fn valid() {
+        let schema = Schema::parse(SCHEMA).unwrap();
+        let image = image();
+        let report = inspect(&image, 0, &schema, "Image").unwrap();
+
+        assert!(report.is_ok(), "{:?}", report.issues);
+        // the second node is reached as both `Image.nodes[1]` and `Node`
+        assert_eq!(report.edges.len(), 4);
+        assert_eq!(report.nulls.len(), 2);
+        assert!(report.objects.iter().any(|object| object.ty == "[u8; 5]" && object.pos == image.len() - 5));
+
+        let mut dump = Vec::new();
+        report.write_hexdump(&image, &mut dump).unwrap();
+        let dump = String::from_utf8(dump).unwrap();
+
+        assert!(dump.contains("Image.nodes[0].next -> 0x"));
+        assert!(dump.contains("Image.nodes[1].next -> null"));
+        assert!(dump.contains("|ab cdefg"));
+
+        let mut dot = Vec::new();
+        report.write_dot(&mut dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+
+        assert!(dot.starts_with("digraph image {"));
+        assert!(dot.contains("n0 -> n1 [label=\"Image.nodes[0].next\"];"));
+        assert!(!dot.contains("color=red"));
+    }
+
+    #[test]
+    fn corrupt() {
+        let schema = Schema::parse(SCHEMA).unwrap();
+        let node = std::mem::size_of::<Node>();
+        let next = next_pos(&schema);
+        let mut image = image();
+
+        set_next(&mut image, next, 1000);
+        let report = inspect(&image, 0, &schema, "Image").unwrap();
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].kind, IssueKind::OutOfBounds);
+        assert_eq!(report.issues[0].pos, next);
+
+        let mut dot = Vec::new();
+        report.write_dot(&mut dot).unwrap();
+        assert!(String::from_utf8(dot).unwrap().contains("out of bounds\", color=red"));
+
+        set_next(&mut image, next, -(next as i32) - 1);
+        let report = inspect(&image, 0, &schema, "Image").unwrap();
+        assert_eq!(report.issues[0].kind, IssueKind::OutOfBounds);
+
+        set_next(&mut image, next, node as i32 - next as i32 + 4);
+        let report = inspect(&image, 0, &schema, "Image").unwrap();
+        assert_eq!(report.issues[0].kind, IssueKind::Misaligned);
+
+        // a node that starts inside of the first node, and ends inside of the second
+        let middle = node - std::mem::align_of::<Node>();
+        set_next(&mut image, next, middle as i32 - next as i32);
+        let report = inspect(&image, 0, &schema, "Node").unwrap();
+        assert!(report.issues.iter().any(|issue| issue.kind == IssueKind::Overlapping && issue.pos == middle));
+
+        assert!(inspect(&image, 0, &schema, "Missing").is_err());
+        assert_eq!(inspect(&image, image.len(), &schema, "Node").unwrap().issues[0].kind, IssueKind::OutOfBounds);
+    }
+}
+
+#[cfg(all(feature = "mmap", unix))]
+mod mmap {
+    use super::*;
+    use crate::mmap::*;
+
+    #[repr(C)]
+    struct Root {
+        values: [u32; 4],
+        first: RelPtr<u32, i32>,
+        last: RelPtr<u32, i32>,
+    }
+
+    unsafe impl Validate for Root {
+        fn validate(validator: &mut Validator<'_>, pos: usize) -> Result<(), MmapError> {
+            validator.check::<[u32; 4]>(pos + offset_of!(Root, values))?;
+            validator.check::<RelPtr<u32, i32>>(pos + offset_of!(Root, first))?;
+            validator.check::<RelPtr<u32, i32>>(pos + offset_of!(Root, last))
+        }
+    }
+
+    #[repr(C)]
+    struct Image {
+        header: Header,
+        root: Root,
+    }
+
+    fn image() -> Box<Image> {
+        let mut image = Box::new(Image {
+            header: Header::new::<i32>(offset_of!(Image, root) as u64),
+            root: Root { values: [1, 2, 3, 4], first: RelPtr::null(), last: RelPtr::null() },
+        });
+
+        image.root.first.set(&mut image.root.values[0]).unwrap();
+        image.root.last.set(&mut image.root.values[3]).unwrap();
+
+        image
+    }
+
+    fn bytes(image: &Image) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(image as *const Image as *const u8, std::mem::size_of::<Image>()) }
+    }
+
+    #[test]
+    fn load_file() {
+        let image = image();
+        let path = std::env::temp_dir().join(format!("rel-ptr-mmap-{}.bin", std::process::id()));
+
+        std::fs::write(&path, bytes(&image)).unwrap();
+
+        let map = unsafe { Mmap::open(&path) };
+
+        std::fs::remove_file(&path).unwrap();
+
+        let map = map.unwrap();
+        let root = map.root::<Root>().unwrap();
+
+        assert_eq!(root.values, [1, 2, 3, 4]);
+        assert_eq!(unsafe { *root.first.as_ref_unchecked() }, 1);
+        assert_eq!(unsafe { *root.last.as_ref_unchecked() }, 4);
+        assert!(matches!(map.root::<RelPtr<u32, i16>>(), Err(MmapError::DeltaWidth { expected: 2, found: 4 })));
+    }
+
+    #[test]
+    fn reject_corrupt() {
+        let mut image = image();
+
+        assert!(load_root::<Root>(bytes(&image)).is_ok());
+
+        image.root.last = RelPtr::from(1000);
+
+        assert!(matches!(load_root::<Root>(bytes(&image)), Err(MmapError::OutOfBounds(_))));
+
+        image.root.last = RelPtr::from(-2);
+
+        assert!(matches!(load_root::<Root>(bytes(&image)), Err(MmapError::Misaligned(_))));
+
+        image.header.magic[0] = 0;
+
+        assert!(matches!(load_root::<Root>(bytes(&image)), Err(MmapError::BadMagic)));
+        assert!(matches!(load_root::<Root>(&[0; 4]), Err(MmapError::TooSmall)));
+    }
+
+    #[test]
+    fn other_endianness() {
+        let mut image = image();
+
+        // an image from a machine with the other byte order has its version byte swapped
+        image.header.endian = if image.header.endian == LITTLE_ENDIAN { BIG_ENDIAN } else { LITTLE_ENDIAN };
+        image.header.version = VERSION.swap_bytes();
+
+        assert!(matches!(load_root::<Root>(bytes(&image)), Err(MmapError::Endianness)));
+    }
+
+    #[test]
+    fn zero_sized_slice() {
+        #[repr(C)]
+        struct Units {
+            header: Header,
+            units: RelPtr<[()], i32>,
+        }
+
+        let mut image = Units { header: Header::new::<i32>(offset_of!(Units, units) as u64), units: RelPtr::null() };
+
+        // a slice of zero sized values can be as long as it likes, this must not check every element
+        let units = unsafe { std::slice::from_raw_parts_mut(&mut image.header as *mut Header as *mut (), usize::MAX) };
+
+        image.units.set(units).unwrap();
+
+        let bytes = unsafe { std::slice::from_raw_parts(&image as *const Units as *const u8, std::mem::size_of::<Units>()) };
+        let units = load_root::<RelPtr<[()], i32>>(bytes).unwrap();
+
+        assert_eq!(unsafe { units.as_ref_unchecked() }.len(), usize::MAX);
+    }
+
+    #[test]
+    fn rel_const() {
+        #[repr(C)]
+        struct Shared {
+            header: Header,
+            value: u32,
+            ptr: RelConst<u32, i32>,
+        }
+
+        let mut image = Shared { header: Header::new::<i32>(offset_of!(Shared, ptr) as u64), value: 7, ptr: RelConst::null() };
+        let bytes = |image: &Shared| unsafe { std::slice::from_raw_parts(image as *const Shared as *const u8, std::mem::size_of::<Shared>()) };
+
+        image.ptr.set(&image.value).unwrap();
+
+        // a `RelConst` is checked just like the `RelPtr` it wraps
+        let ptr = load_root::<RelConst<u32, i32>>(bytes(&image)).unwrap();
+
+        assert_eq!(unsafe { *ptr.as_ref_unchecked() }, 7);
+
+        image.ptr = RelConst::from(RelPtr::from(1000));
+
+        assert!(matches!(load_root::<RelConst<u32, i32>>(bytes(&image)), Err(MmapError::OutOfBounds(_))));
+
+        image.ptr = RelConst::from(RelPtr::from(-2));
+
+        assert!(matches!(load_root::<RelConst<u32, i32>>(bytes(&image)), Err(MmapError::Misaligned(_))));
+    }
+}
+
+#[cfg(all(feature = "shm", target_os = "linux"))]
+mod shm {
+    use super::*;
+    use crate::shm::*;
+
+    #[derive(Clone, Copy)]
+    struct Node {
+        value: u32,
+        next: RelPtr<Node, i32>,
+    }
+
+    #[derive(Clone, Copy)]
+    struct Root {
+        head: RelPtr<Node, i32>,
+    }
+
+    fn push(heap: &mut SharedHeap<Root>, value: u32) -> bool {
+        let (root, alloc) = heap.split();
+        let node = match alloc.alloc(Node { value, next: RelPtr::null() }) {
+            Some(node) => node,
+            None => return false,
+        };
+
+        if let Some(head) = unsafe { root.head.as_mut() } {
+            if node.next.set(head).is_err() {
+                return false;
+            }
+        }
+
+        root.head.set(node).is_ok()
+    }
+
+    fn sum(heap: &SharedHeap<Root>) -> u32 {
+        let mut sum = 0;
+        let mut node = unsafe { heap.root().head.as_ref() };
+
+        while let Some(n) = node {
+            sum += n.value;
+            node = unsafe { n.next.as_ref() };
+        }
+
+        sum
+    }
+
+    #[test]
+    fn remap() {
+        let mut heap = SharedHeap::create(4096, Root { head: RelPtr::null() }).unwrap();
+
+        assert!(push(&mut heap, 1));
+        assert!(push(&mut heap, 2));
+
+        let mut other = unsafe { SharedHeap::<Root>::open(heap.region().remap().unwrap()).unwrap() };
+
+        assert_ne!(other.region().as_ptr(), heap.region().as_ptr());
+        assert_eq!(sum(&other), 3);
+        assert!(push(&mut other, 4));
+        assert_eq!(sum(&heap), 7);
+        assert_eq!(heap.used(), other.used());
+    }
+
+    #[test]
+    fn open_existing() {
+        let name = format!("/rel-ptr-test-{}", std::process::id());
+        let size = |region: &SharedRegion| {
+            let mut stat = unsafe { std::mem::zeroed::<libc::stat>() };
+
+            assert_eq!(unsafe { libc::fstat(region.fd(), &mut stat) }, 0);
+            stat.st_size
+        };
+
+        let first = SharedRegion::open(&name, 8192).unwrap();
+
+        unsafe { *first.as_ptr().add(8000) = 7 };
+
+        // opening it again with a smaller length doesn't shrink it
+        let second = SharedRegion::open(&name, 4096).unwrap();
+
+        assert_eq!(second.len(), 4096);
+        assert_eq!(size(&first), 8192);
+        assert_eq!(unsafe { *first.as_ptr().add(8000) }, 7);
+
+        // and a larger length than the object is an error
+        let err = SharedRegion::open(&name, 16384).unwrap_err();
+
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(size(&first), 8192);
+
+        SharedRegion::unlink(&name).unwrap();
+    }
+
+    #[test]
+    fn full() {
+        let heap = SharedHeap::create(128, Root { head: RelPtr::null() }).unwrap();
+
+        assert!(heap.alloc([0u8; 64]).is_some());
+        assert!(heap.alloc([0u8; 64]).is_none());
+        assert!(SharedHeap::create(8, Root { head: RelPtr::null() }).is_err());
+    }
+
+    #[test]
+    fn fork() {
+        let mut heap = SharedHeap::create(4096, Root { head: RelPtr::null() }).unwrap();
+
+        assert!(push(&mut heap, 1));
+        assert!(push(&mut heap, 2));
+
+        match unsafe { libc::fork() } {
+            -1 => panic!("fork failed: {}", std::io::Error::last_os_error()),
+            0 => {
+                // map the region at another address, and extend the list from there
+                let code = match heap.region().remap().and_then(|region| unsafe { SharedHeap::<Root>::open(region) }) {
+                    Ok(mut other) => if sum(&other) == 3 && push(&mut other, 10) { 0 } else { 1 },
+                    Err(_) => 2,
+                };
+
+                unsafe { libc::_exit(code) }
+            },
+            pid => {
+                let mut status = 0;
+
+                assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+                assert!(libc::WIFEXITED(status));
+                assert_eq!(libc::WEXITSTATUS(status), 0);
+                assert_eq!(sum(&heap), 13);
+            },
+        }
+    }
+}
+
 #[cfg(feature = "nightly")]
 mod nightly {
     use super::*;
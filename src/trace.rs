@@ -0,0 +1,226 @@
+use super::{RelPtr, TaggedRelPtr, MetaData, IntegerDelta};
+
+/**
+ * Visits relative pointers, see `RelTrace::trace`
+ */
+pub trait RelVisitor {
+    /**
+     * Visit a relative pointer, its address can be found from `ptr`,
+     * and its offset and metadata with `RelPtr::offset` and `RelPtr::metadata`.
+     * A null relative pointer has an offset of zero
+     *
+     * The pointee doesn't have to implement `RelTrace`, so it isn't traced
+     */
+    fn visit<T: ?Sized + MetaData, I: IntegerDelta>(&mut self, ptr: &RelPtr<T, I>);
+
+    /**
+     * Visit a tagged relative pointer, its offset without the tag can be found with
+     * `TaggedRelPtr::offset`, and its tag with `TaggedRelPtr::tag`
+     *
+     * By default this visits the underlying relative pointer, whose offset still has
+     * the tag in its low bits, so visitors that use the offset should override this
+     */
+    #[inline]
+    fn visit_tagged<T: ?Sized + MetaData, I: IntegerDelta, const BITS: u32>(&mut self, ptr: &TaggedRelPtr<T, I, BITS>) {
+        self.visit(&ptr.0)
+    }
+}
+
+/**
+ * Visits relative pointers mutably, see `RelTrace::trace_mut`
+ */
+pub trait RelVisitorMut {
+    /// Visit a relative pointer, see `RelVisitor::visit`
+    fn visit_mut<T: ?Sized + MetaData, I: IntegerDelta>(&mut self, ptr: &mut RelPtr<T, I>);
+
+    /// Visit a tagged relative pointer, see `RelVisitor::visit_tagged`
+    #[inline]
+    fn visit_tagged_mut<T: ?Sized + MetaData, I: IntegerDelta, const BITS: u32>(&mut self, ptr: &mut TaggedRelPtr<T, I, BITS>) {
+        self.visit_mut(&mut ptr.0)
+    }
+}
+
+/**
+ * A type that can list all of the relative pointers that it contains
+ *
+ * This is the basis for generic tools that need to find every relative
+ * pointer in a value, like validation, relocation and debugging.
+ * It is implemented for primitives, `RelPtr`, `TaggedRelPtr`, arrays, slices, tuples, `Option`,
+ * and (with `std`) `Box` and `Vec`. Use `impl_rel_trace!` to implement it for
+ * your own types.
+ *
+ * Only relative pointers that use an `IntegerDelta` can be traced, and only the
+ * relative pointers inside of the value are visited, not the relative pointers
+ * inside of their pointees
+ */
+pub trait RelTrace {
+    /// Visit every relative pointer inside of `self`
+    fn trace<V: RelVisitor + ?Sized>(&self, visitor: &mut V);
+
+    /// Visit every relative pointer inside of `self` mutably
+    fn trace_mut<V: RelVisitorMut + ?Sized>(&mut self, visitor: &mut V);
+}
+
+impl<T: ?Sized + MetaData, I: IntegerDelta> RelTrace for RelPtr<T, I> {
+    #[inline]
+    fn trace<V: RelVisitor + ?Sized>(&self, visitor: &mut V) {
+        visitor.visit(self)
+    }
+
+    #[inline]
+    fn trace_mut<V: RelVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        visitor.visit_mut(self)
+    }
+}
+
+macro_rules! impl_rel_trace_leaf {
+    ($($type:ty),* $(,)?) => {$(
+        impl RelTrace for $type {
+            #[inline]
+            fn trace<V: RelVisitor + ?Sized>(&self, _: &mut V) {}
+
+            #[inline]
+            fn trace_mut<V: RelVisitorMut + ?Sized>(&mut self, _: &mut V) {}
+        }
+    )*};
+}
+
+impl_rel_trace_leaf! {
+    (), bool, char, str,
+    u8, u16, u32, u64, u128, usize,
+    i8, i16, i32, i64, i128, isize,
+    f32, f64,
+}
+
+impl<T: RelTrace> RelTrace for [T] {
+    fn trace<V: RelVisitor + ?Sized>(&self, visitor: &mut V) {
+        for item in self {
+            item.trace(visitor);
+        }
+    }
+
+    fn trace_mut<V: RelVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        for item in self {
+            item.trace_mut(visitor);
+        }
+    }
+}
+
+impl<T: RelTrace, const N: usize> RelTrace for [T; N] {
+    #[inline]
+    fn trace<V: RelVisitor + ?Sized>(&self, visitor: &mut V) {
+        self[..].trace(visitor)
+    }
+
+    #[inline]
+    fn trace_mut<V: RelVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        self[..].trace_mut(visitor)
+    }
+}
+
+impl<T: RelTrace> RelTrace for Option<T> {
+    #[inline]
+    fn trace<V: RelVisitor + ?Sized>(&self, visitor: &mut V) {
+        if let Some(value) = self {
+            value.trace(visitor)
+        }
+    }
+
+    #[inline]
+    fn trace_mut<V: RelVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        if let Some(value) = self {
+            value.trace_mut(visitor)
+        }
+    }
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T: ?Sized + RelTrace> RelTrace for Box<T> {
+    #[inline]
+    fn trace<V: RelVisitor + ?Sized>(&self, visitor: &mut V) {
+        T::trace(self, visitor)
+    }
+
+    #[inline]
+    fn trace_mut<V: RelVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        T::trace_mut(self, visitor)
+    }
+}
+
+#[cfg(not(feature = "no_std"))]
+impl<T: RelTrace> RelTrace for Vec<T> {
+    #[inline]
+    fn trace<V: RelVisitor + ?Sized>(&self, visitor: &mut V) {
+        self[..].trace(visitor)
+    }
+
+    #[inline]
+    fn trace_mut<V: RelVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        self[..].trace_mut(visitor)
+    }
+}
+
+macro_rules! impl_rel_trace_tuple {
+    ($($name:ident $index:tt),*) => {
+        impl<$($name: RelTrace),*> RelTrace for ($($name,)*) {
+            #[inline]
+            fn trace<V: RelVisitor + ?Sized>(&self, visitor: &mut V) {
+                $(self.$index.trace(visitor);)*
+            }
+
+            #[inline]
+            fn trace_mut<V: RelVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+                $(self.$index.trace_mut(visitor);)*
+            }
+        }
+    };
+}
+
+impl_rel_trace_tuple! { A 0 }
+impl_rel_trace_tuple! { A 0, B 1 }
+impl_rel_trace_tuple! { A 0, B 1, C 2 }
+impl_rel_trace_tuple! { A 0, B 1, C 2, D 3 }
+impl_rel_trace_tuple! { A 0, B 1, C 2, D 3, E 4 }
+impl_rel_trace_tuple! { A 0, B 1, C 2, D 3, E 4, F 5 }
+impl_rel_trace_tuple! { A 0, B 1, C 2, D 3, E 4, F 5, G 6 }
+impl_rel_trace_tuple! { A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7 }
+
+/**
+ * Implement `RelTrace` for a struct, by tracing each of the listed fields
+ *
+ * This takes the place of a derive, fields that don't contain relative pointers
+ * can be left out. Generic parameters go in square brackets after `impl`.
+ *
+ * ```rust
+ * # fn main() {
+ * use rel_ptr::{RelPtr, RelTrace, impl_rel_trace};
+ *
+ * struct Node<T> {
+ *     value: T,
+ *     next: RelPtr<Node<T>, i32>,
+ *     children: [Option<RelPtr<Node<T>, i32>>; 2],
+ * }
+ *
+ * impl_rel_trace!(impl[T] Node<T> { next, children });
+ * # }
+ * ```
+ */
+#[macro_export]
+macro_rules! impl_rel_trace {
+    (impl [$($gen:tt)*] $type:ty { $($field:tt),* $(,)? }) => {
+        impl<$($gen)*> $crate::RelTrace for $type {
+            #[allow(unused_variables)]
+            fn trace<V: $crate::RelVisitor + ?Sized>(&self, visitor: &mut V) {
+                $($crate::RelTrace::trace(&self.$field, visitor);)*
+            }
+
+            #[allow(unused_variables)]
+            fn trace_mut<V: $crate::RelVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+                $($crate::RelTrace::trace_mut(&mut self.$field, visitor);)*
+            }
+        }
+    };
+    ($type:ty { $($field:tt),* $(,)? }) => {
+        $crate::impl_rel_trace!(impl[] $type { $($field),* });
+    };
+}
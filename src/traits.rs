@@ -1,6 +1,8 @@
 
 use std::ptr::NonNull;
 
+use crate::IntegerDeltaError;
+
 /// A nullable pointer, using NonNull<T>
 pub type Ptr<T> = Option<NonNull<T>>;
 
@@ -13,6 +15,11 @@ pub type Ptr<T> = Option<NonNull<T>>;
  * are detailed in each function
  *
  * This trait is intended to be used with `RelPtr`
+ *
+ * # Safety
+ *
+ * The invariants detailed on `Delta::sub`, `Delta::sub_unchecked`
+ * and `Delta::add` must hold for all pointers
  */
 pub unsafe trait Delta: Copy + Eq {
     /// Error of `Delta::sub`
@@ -50,7 +57,7 @@ pub unsafe trait Delta: Copy + Eq {
      *  }
      * ```
      * 
-     * Safety:
+     * # Safety
      * 
      * If the difference between `a` and `b` is not
      * representable by `Self` is UB
@@ -77,6 +84,18 @@ pub unsafe trait Delta: Copy + Eq {
      * TODO
      */
     unsafe fn add(self, a: *const u8) -> *mut u8;
+
+    /**
+     * The offset in bytes, if it is stored as a plain number of bytes
+     *
+     * This is only used to show the target of a relative pointer in its
+     * alternate `Debug` output, so it defaults to `None`. The built-in
+     * `IntegerDelta`s return `Some(self.to_isize())`
+     */
+    #[inline]
+    fn byte_offset(self) -> Option<isize> {
+        None
+    }
 }
 
 /// A index which can contain null
@@ -95,12 +114,59 @@ pub trait Nullable: Delta {
     const NULL: Self;
 }
 
+/**
+ * A `Delta` that stores its offset as a plain number of bytes
+ *
+ * This gives access to the raw offset, so that relative pointers can
+ * be inspected and manipulated without resolving them
+ *
+ * # Safety
+ *
+ * `Delta::add(d, a)` must be the same as `a.offset(d.to_isize())`,
+ * and `Self::from_isize(d.to_isize())` must be `Ok(d)` for all `d`
+ * where `d.try_to_isize()` is `Ok`. `to_isize` must panic for the other `d`,
+ * it must never truncate the offset
+ */
+pub unsafe trait IntegerDelta: Delta<Error = IntegerDeltaError> {
+    /// The number of bits used to store the offset
+    const BITS: u32;
+
+    /**
+     * Get the offset in bytes
+     *
+     * # Panics
+     *
+     * If the offset doesn't fit in an `isize`, see `IntegerDelta::try_to_isize`
+     */
+    fn to_isize(self) -> isize;
+
+    /**
+     * Get the offset in bytes, if it fits in an `isize`
+     *
+     * Offsets that are wider than `isize`, like `i128`, can store offsets that
+     * don't fit, and these return `Err` instead of being truncated
+     */
+    #[inline]
+    fn try_to_isize(self) -> Result<isize, IntegerDeltaError> {
+        Ok(self.to_isize())
+    }
+
+    /// Try to store the given offset in bytes,
+    /// if it is out of range an error is returned
+    fn from_isize(del: isize) -> Result<Self, IntegerDeltaError>;
+}
+
 /**
  * A trait to abstract over the sizedness of types,
  * and to access metadata about a type
  *
  * If [Custom DST](https://github.com/rust-lang/rfcs/pull/2594) lands and stablizes,
  * then it will replace `MetaData`
+ *
+ * # Safety
+ *
+ * `MetaData::compose` must produce a pointer to the same value that
+ * was passed to `MetaData::data`, when given that value's address
  */
 pub unsafe trait MetaData {
     /// the type of meta data a type carries
@@ -114,6 +180,11 @@ pub unsafe trait MetaData {
     /// it is guarenteed that the metadata is
     /// * `ptr == None` `Self::Data` is undefined
     /// * `ptr != None` generated from `MetaData::data`
+    ///
+    /// # Safety
+    ///
+    /// `data` must have been generated by `MetaData::data` from the value
+    /// that `ptr` points to
     unsafe fn compose(ptr: Ptr<u8>, data: Self::Data) -> Ptr<Self>;
 }
 
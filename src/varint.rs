@@ -0,0 +1,239 @@
+/*!
+ * Variable length relative offsets, for serialized images
+ *
+ * In memory, a `RelPtr` needs a fixed size offset. But in a serialized stream
+ * most offsets are tiny, so they can be stored as a [LEB128](https://en.wikipedia.org/wiki/LEB128)
+ * varint of the [zigzag](https://developers.google.com/protocol-buffers/docs/encoding#signed-integers)
+ * encoded offset. Like a `RelPtr`, an offset is relative to the position it is stored at,
+ * so the stream can be moved as a whole.
+ */
+
+use std::fmt;
+
+/// The number of bits in an `isize`
+const BITS: u32 = (std::mem::size_of::<isize>() * 8) as u32;
+
+/// The maximum number of bytes a varint offset can use
+pub const MAX_VARINT_LEN: usize = (BITS as usize + 6) / 7;
+
+/// Errors from reading or writing varint offsets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VarintError {
+    /// The stream ended in the middle of a varint
+    UnexpectedEnd,
+
+    /// The varint does not fit in an `isize`
+    Overflow,
+
+    /// The offset points outside of the stream, this holds the target position
+    OutOfBounds(isize),
+
+    /// There is not enough space left in the buffer to write to
+    BufferFull,
+}
+
+#[cfg(not(feature = "no_std"))]
+impl std::error::Error for VarintError {}
+
+impl fmt::Display for VarintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VarintError::UnexpectedEnd => write!(f, "Stream ended in the middle of a varint"),
+            VarintError::Overflow => write!(f, "Varint does not fit in an `isize`"),
+            VarintError::OutOfBounds(pos) => write!(f, "Offset points to {}, which is outside of the stream", pos),
+            VarintError::BufferFull => write!(f, "Not enough space left in the buffer"),
+        }
+    }
+}
+
+/// Map signed offsets to unsigned, so that small negative offsets stay small
+#[inline]
+pub fn zigzag(offset: isize) -> usize {
+    ((offset << 1) ^ (offset >> (BITS - 1))) as usize
+}
+
+/// The inverse of `zigzag`
+#[inline]
+pub fn unzigzag(value: usize) -> isize {
+    ((value >> 1) as isize) ^ -((value & 1) as isize)
+}
+
+/// The number of bytes needed to store `offset` as a varint
+pub fn encoded_len(offset: isize) -> usize {
+    let bits = BITS - zigzag(offset).leading_zeros();
+
+    std::cmp::max(1, (bits as usize + 6) / 7)
+}
+
+/**
+ * Reads varint offsets out of a byte stream
+ *
+ * ```rust
+ * # fn main() {
+ * use rel_ptr::varint::{VarintReader, VarintWriter};
+ *
+ * let mut buf = [0; 16];
+ * let mut writer = VarintWriter::new(&mut buf);
+ *
+ * writer.write_bytes(b"hi").unwrap();
+ * writer.write_rel(0).unwrap();
+ *
+ * let mut reader = VarintReader::new(&buf);
+ *
+ * reader.seek(2);
+ *
+ * assert_eq!(reader.read_rel(), Ok(0));
+ * # }
+ * ```
+ */
+#[derive(Debug, Clone)]
+pub struct VarintReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> VarintReader<'a> {
+    /// Create a new reader at the start of `buf`
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// The current position in the stream
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Move to the given position in the stream
+    pub fn seek(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    /**
+     * Read a varint offset, and move past it
+     *
+     * On error, the position does not change
+     */
+    pub fn read_offset(&mut self) -> Result<isize, VarintError> {
+        let mut pos = self.pos;
+        let mut value = 0usize;
+        let mut shift = 0;
+
+        loop {
+            let byte = *self.buf.get(pos).ok_or(VarintError::UnexpectedEnd)?;
+            let bits = (byte & 0x7f) as usize;
+            pos += 1;
+
+            if shift >= BITS || (bits << shift) >> shift != bits {
+                return Err(VarintError::Overflow);
+            }
+
+            value |= bits << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+
+        self.pos = pos;
+
+        Ok(unzigzag(value))
+    }
+
+    /**
+     * Read a varint offset, and resolve it relative to the position it was stored at,
+     * the target is checked to be inside of the stream
+     *
+     * On error, the position does not change
+     */
+    pub fn read_rel(&mut self) -> Result<usize, VarintError> {
+        let start = self.pos;
+        let offset = self.read_offset()?;
+        let target = (start as isize).checked_add(offset).ok_or(VarintError::Overflow);
+
+        match target {
+            Ok(target) if target >= 0 && (target as usize) < self.buf.len() => Ok(target as usize),
+            Ok(target) => {
+                self.pos = start;
+                Err(VarintError::OutOfBounds(target))
+            },
+            Err(err) => {
+                self.pos = start;
+                Err(err)
+            }
+        }
+    }
+}
+
+/**
+ * Writes varint offsets into a byte buffer,
+ * each offset uses the shortest possible encoding
+ */
+#[derive(Debug)]
+pub struct VarintWriter<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> VarintWriter<'a> {
+    /// Create a new writer at the start of `buf`
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    /// The current position in the buffer
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// The bytes that were written so far
+    pub fn written(&self) -> &[u8] {
+        &self.buf[..self.pos]
+    }
+
+    /// Write raw bytes
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), VarintError> {
+        let end = self.pos + bytes.len();
+
+        self.buf.get_mut(self.pos..end)
+            .ok_or(VarintError::BufferFull)?
+            .copy_from_slice(bytes);
+        self.pos = end;
+
+        Ok(())
+    }
+
+    /**
+     * Write a varint offset
+     *
+     * On error, nothing is written
+     */
+    pub fn write_offset(&mut self, offset: isize) -> Result<(), VarintError> {
+        let len = encoded_len(offset);
+        let out = self.buf.get_mut(self.pos..self.pos + len).ok_or(VarintError::BufferFull)?;
+        let mut value = zigzag(offset);
+
+        for byte in out.iter_mut() {
+            *byte = (value & 0x7f) as u8 | 0x80;
+            value >>= 7;
+        }
+
+        out[len - 1] &= 0x7f;
+        self.pos += len;
+
+        Ok(())
+    }
+
+    /**
+     * Write the offset from the current position to `target`,
+     * so that `VarintReader::read_rel` at this position resolves to `target`
+     *
+     * On error, nothing is written
+     */
+    pub fn write_rel(&mut self, target: usize) -> Result<(), VarintError> {
+        let offset = (target as isize).checked_sub(self.pos as isize).ok_or(VarintError::Overflow)?;
+
+        self.write_offset(offset)
+    }
+}